@@ -27,7 +27,7 @@ main! = |args| {
 	Cmd.new("ls").args(["-l", "-a"]).exec_cmd!().map_err(|e| Cmd(e))?
 	Stderr.line!("`Cmd`: ls -la -> output: ${Str.inspect(Dir.list!("/home/johannes"))}")
 
-	response = Http.send!({ method: Get, headers: [], uri: "https://google.com", body: [] }).map_err(|e| Get(e))?
+	response = Http.send!({ method: Get, headers: [], uri: "https://google.com", body: Bytes([]), connect_timeout_millis: 5_000, timeout_millis: 0, redirect: Follow(10) }).map_err(|e| Get(e))?
 	Stderr.line!("`Http`: send -> body: ${Str.from_utf8_lossy(response.body)}")
 
 	Ok({})
@@ -1,8 +1,15 @@
+import Process
+
 Cmd :: {
 	args : List(Str),
 	clear_envs : Bool,
+	cwd : Str,
 	envs : List(Str),
 	program : Str,
+	stderr : [Append(Str), Default, Inherit, Null, Piped, ToStdout, Truncate(Str)],
+	stdin : [Bytes(List(U8)), Default, File(Str), Inherit, Null],
+	stdout : [Append(Str), Default, Inherit, Null, Piped, ToStdout, Truncate(Str)],
+	timeout_millis : U64,
 }.{
 	IOErr := [NotFound, PermissionDenied, BrokenPipe, AlreadyExists, Interrupted, Unsupported, OutOfMemory, Other(Str)]
 
@@ -15,8 +22,13 @@ Cmd :: {
 	new = |program| {
 		args: [],
 		clear_envs: Bool.False,
+		cwd: "",
 		envs: [],
 		program,
+		stderr: Default,
+		stdin: Default,
+		stdout: Default,
+		timeout_millis: 0,
 	}
 
 	## Add a single argument to the command.
@@ -28,8 +40,13 @@ Cmd :: {
 	arg = |cmd, a| {
 		args: List.append(cmd.args, a),
 		clear_envs: cmd.clear_envs,
+		cwd: cmd.cwd,
 		envs: cmd.envs,
 		program: cmd.program,
+		stderr: cmd.stderr,
+		stdin: cmd.stdin,
+		stdout: cmd.stdout,
+		timeout_millis: cmd.timeout_millis,
 	}
 
 	## Add multiple arguments to the command.
@@ -41,8 +58,13 @@ Cmd :: {
 	args = |cmd, new_args| {
 		args: List.concat(cmd.args, new_args),
 		clear_envs: cmd.clear_envs,
+		cwd: cmd.cwd,
 		envs: cmd.envs,
 		program: cmd.program,
+		stderr: cmd.stderr,
+		stdin: cmd.stdin,
+		stdout: cmd.stdout,
+		timeout_millis: cmd.timeout_millis,
 	}
 
 	## Add a single environment variable to the command.
@@ -54,8 +76,13 @@ Cmd :: {
 	env = |cmd, key, value| {
 		args: cmd.args,
 		clear_envs: cmd.clear_envs,
+		cwd: cmd.cwd,
 		envs: List.concat(cmd.envs, [key, value]),
 		program: cmd.program,
+		stderr: cmd.stderr,
+		stdin: cmd.stdin,
+		stdout: cmd.stdout,
+		timeout_millis: cmd.timeout_millis,
 	}
 
 	## Add multiple environment variables to the command.
@@ -69,8 +96,13 @@ Cmd :: {
 		{
 			args: cmd.args,
 			clear_envs: cmd.clear_envs,
+			cwd: cmd.cwd,
 			envs: List.concat(cmd.envs, flat),
 			program: cmd.program,
+			stderr: cmd.stderr,
+			stdin: cmd.stdin,
+			stdout: cmd.stdout,
+			timeout_millis: cmd.timeout_millis,
 		}
 	}
 
@@ -84,20 +116,181 @@ Cmd :: {
 	clear_envs = |cmd| {
 		args: cmd.args,
 		clear_envs: Bool.True,
+		cwd: cmd.cwd,
 		envs: cmd.envs,
 		program: cmd.program,
+		stderr: cmd.stderr,
+		stdin: cmd.stdin,
+		stdout: cmd.stdout,
+		timeout_millis: cmd.timeout_millis,
 	}
 
-	## Execute a command and return its exit code.
-	## Stdin, stdout, and stderr are inherited from the parent process.
+	## Set the working directory the command is run in.
+	## By default it is inherited from the parent process.
+	##
+	## ```roc
+	## cmd = Cmd.new("git").arg("status").cwd("path/to/repo")
+	## ```
+	cwd : Cmd, Str -> Cmd
+	cwd = |cmd, path| {
+		args: cmd.args,
+		clear_envs: cmd.clear_envs,
+		cwd: path,
+		envs: cmd.envs,
+		program: cmd.program,
+		stderr: cmd.stderr,
+		stdin: cmd.stdin,
+		stdout: cmd.stdout,
+		timeout_millis: cmd.timeout_millis,
+	}
+
+	## Write the given bytes to the stdin of the command.
+	## The input is written while the output is collected, so large inputs don't block the command.
+	##
+	## ```roc
+	## cmd = Cmd.new("jq").arg(".name").stdin_bytes(json_bytes)
+	## ```
+	stdin_bytes : Cmd, List(U8) -> Cmd
+	stdin_bytes = |cmd, bytes| {
+		args: cmd.args,
+		clear_envs: cmd.clear_envs,
+		cwd: cmd.cwd,
+		envs: cmd.envs,
+		program: cmd.program,
+		stderr: cmd.stderr,
+		stdin: Bytes(bytes),
+		stdout: cmd.stdout,
+		timeout_millis: cmd.timeout_millis,
+	}
+
+	## Write the given string to the stdin of the command.
+	##
+	## ```roc
+	## cmd = Cmd.new("psql").stdin_str("SELECT 1;")
+	## ```
+	stdin_str : Cmd, Str -> Cmd
+	stdin_str = |cmd, s| stdin_bytes(cmd, Str.to_utf8(s))
+
+	## Set where the stdin of the command comes from:
+	##
+	## - `Bytes(bytes)` writes the bytes, like [Cmd.stdin_bytes]
+	## - `Default` depends on how the command is run, see below
+	## - `File(path)` reads the file at `path`
+	## - `Inherit` reads the stdin of the parent process
+	## - `Null` is empty
+	##
+	## By default, stdin is inherited by [Cmd.exec_exit_code!] and [Cmd.spawn!] and empty
+	## for the functions that capture the output.
+	##
+	## ```roc
+	## Cmd.new("sort").stdin(File("names.txt")).exec_cmd!()?
+	## ```
+	stdin : Cmd, [Bytes(List(U8)), Default, File(Str), Inherit, Null] -> Cmd
+	stdin = |cmd, redirect| {
+		args: cmd.args,
+		clear_envs: cmd.clear_envs,
+		cwd: cmd.cwd,
+		envs: cmd.envs,
+		program: cmd.program,
+		stderr: cmd.stderr,
+		stdin: redirect,
+		stdout: cmd.stdout,
+		timeout_millis: cmd.timeout_millis,
+	}
+
+	## Set where the stdout of the command goes:
+	##
+	## - `Append(path)` appends to the file at `path`, creating it if needed
+	## - `Default` depends on how the command is run, see below
+	## - `Inherit` writes to the stdout of the parent process
+	## - `Null` discards the output
+	## - `Piped` captures the output
+	## - `Truncate(path)` replaces the contents of the file at `path`, creating it if needed
+	##
+	## `ToStdout` is only valid for [Cmd.stderr]; the command fails with `CmdErr(Other(...))`
+	## if it is used for stdout.
+	##
+	## By default, stdout is inherited by [Cmd.exec_exit_code!] and [Cmd.spawn!] and captured
+	## by the other functions. Output that is not captured is returned as empty.
+	##
+	## ```roc
+	## Cmd.new("make").stdout(Truncate("build.log")).stderr(ToStdout).exec_cmd!()?
+	## ```
+	stdout : Cmd, [Append(Str), Default, Inherit, Null, Piped, ToStdout, Truncate(Str)] -> Cmd
+	stdout = |cmd, redirect| {
+		args: cmd.args,
+		clear_envs: cmd.clear_envs,
+		cwd: cmd.cwd,
+		envs: cmd.envs,
+		program: cmd.program,
+		stderr: cmd.stderr,
+		stdin: cmd.stdin,
+		stdout: redirect,
+		timeout_millis: cmd.timeout_millis,
+	}
+
+	## Set where the stderr of the command goes. The options are the same as for [Cmd.stdout],
+	## plus `ToStdout`, which sends stderr wherever stdout goes, like `2>&1` in a shell.
+	##
+	## ```roc
+	## output = Cmd.new("cargo").arg("build").stderr(ToStdout).exec_output!()?
+	## ```
+	stderr : Cmd, [Append(Str), Default, Inherit, Null, Piped, ToStdout, Truncate(Str)] -> Cmd
+	stderr = |cmd, redirect| {
+		args: cmd.args,
+		clear_envs: cmd.clear_envs,
+		cwd: cmd.cwd,
+		envs: cmd.envs,
+		program: cmd.program,
+		stderr: redirect,
+		stdin: cmd.stdin,
+		stdout: cmd.stdout,
+		timeout_millis: cmd.timeout_millis,
+	}
+
+	## Kill the command if it is still running after the given number of milliseconds.
+	## It is first asked to exit with `SIGTERM` and, if it is still running two seconds
+	## later, killed with `SIGKILL`. The execution then fails with `TimedOut`.
+	##
+	## With a timeout, the command runs in its own process group, and the signals are sent
+	## to the whole group, so processes it started in the background are stopped as well.
+	## This also means that it does not receive Ctrl-C from the terminal, and is stopped
+	## if it tries to read from the terminal.
+	##
+	## [Cmd.spawn!], [Cmd.spawn_piped!], [Cmd.exec_each_line!] and [Cmd.exec_replace!] fail with
+	## `CmdErr` if a timeout is set, as nothing would be left to enforce it.
+	##
+	## ```roc
+	## Cmd.new("git").arg("fetch").timeout(60_000).exec_cmd!()?
+	## ```
+	timeout : Cmd, U64 -> Cmd
+	timeout = |cmd, millis| {
+		args: cmd.args,
+		clear_envs: cmd.clear_envs,
+		cwd: cmd.cwd,
+		envs: cmd.envs,
+		program: cmd.program,
+		stderr: cmd.stderr,
+		stdin: cmd.stdin,
+		stdout: cmd.stdout,
+		timeout_millis: millis,
+	}
+
+	## Execute a command and return how it exited.
+	## By default, stdin, stdout and stderr are inherited from the parent process.
+	## Output redirected with `Piped` is discarded.
 	##
 	## ```roc
-	## exit_code = Cmd.new("ls").arg("-l").exec_exit_code!()?
+	## match Cmd.new("ls").arg("-l").exec_exit_code!()? {
+	##     Exited(code) => Stdout.line!("exited with ${code.to_str()}")
+	##     Signaled({ signal, core_dumped }) => Stdout.line!("killed by signal ${signal.to_str()}")
+	## }
 	## ```
-	exec_exit_code! : Cmd => Try(I32, [CmdErr(IOErr)])
+	exec_exit_code! : Cmd => Try(Process.ExitStatus, [CmdErr(IOErr), TimedOut])
 
 	## Execute command and capture stdout/stderr as UTF-8 strings.
 	## Invalid UTF-8 sequences are replaced with the Unicode replacement character.
+	## By default, stdin is empty; see [Cmd.stdin] and [Cmd.stdout] to change that.
 	##
 	## ```roc
 	## cmd_output =
@@ -109,9 +302,148 @@ Cmd :: {
 	## ```
 	exec_output! : Cmd => Try(
 		{ stdout_utf8 : Str, stderr_utf8_lossy : Str },
-		[CmdErr(IOErr), NonZeroExit({ exit_code : I32, stdout_utf8_lossy : Str, stderr_utf8_lossy : Str })],
+		[
+			CmdErr(IOErr),
+			NonZeroExit({ status : Process.ExitStatus, stdout_utf8_lossy : Str, stderr_utf8_lossy : Str }),
+			TimedOut({ stdout_utf8_lossy : Str, stderr_utf8_lossy : Str }),
+		],
+	)
+
+	## Execute command and capture stdout/stderr as raw bytes.
+	## Use this instead of [Cmd.exec_output!] for binary output like archives or images.
+	##
+	## ```roc
+	## cmd_output =
+	##     Cmd.new("tar")
+	##     .args(["-c", "src"])
+	##     .exec_output_bytes!()?
+	##
+	## File.write_bytes!("src.tar", cmd_output.stdout)?
+	## ```
+	exec_output_bytes! : Cmd => Try(
+		{ stdout : List(U8), stderr : List(U8) },
+		[
+			CmdErr(IOErr),
+			NonZeroExit({ status : Process.ExitStatus, stdout : List(U8), stderr : List(U8) }),
+			TimedOut({ stdout : List(U8), stderr : List(U8) }),
+		],
+	)
+
+	## Execute a command on a new [pseudo-terminal](https://en.wikipedia.org/wiki/Pseudoterminal)
+	## with the given window size and capture everything it writes to the terminal.
+	## Use this for programs that behave differently when they are not attached to a terminal,
+	## for example to get colored output.
+	##
+	## Stdout and stderr are combined, and line endings are `\r\n` like on any terminal.
+	## Redirections set with [Cmd.stdin], [Cmd.stdout] and [Cmd.stderr] are ignored.
+	## Bytes set with [Cmd.stdin_bytes] are typed into the terminal, so they are echoed
+	## into the output, followed by Ctrl-D to end the input.
+	##
+	## ```roc
+	## { output_utf8_lossy } = Cmd.new("ls").arg("--color=auto").exec_pty!({ cols: 120, rows: 40 })?
+	## ```
+	exec_pty! : Cmd, { cols : U16, rows : U16 } => Try(
+		{ output_utf8_lossy : Str, status : Process.ExitStatus },
+		[CmdErr(IOErr), TimedOut({ output_utf8_lossy : Str })],
+	)
+
+	## Execute a command on a new [pseudo-terminal](https://en.wikipedia.org/wiki/Pseudoterminal)
+	## that is connected to the terminal of the current program, and wait for it to exit.
+	##
+	## While the command runs, the terminal is in [raw mode](Tty.enable_raw_mode!) so every key
+	## press goes straight to the command, and the window size follows the current terminal.
+	## Redirections and timeouts are ignored.
+	##
+	## ```roc
+	## Cmd.new("git").args(["log", "--oneline"]).exec_pty_interactive!()?
+	## ```
+	exec_pty_interactive! : Cmd => Try(Process.ExitStatus, [CmdErr(IOErr)])
+
+	## Replace the current program with the command, like `exec` in a shell. The command
+	## keeps the process ID and the terminal, and its exit code becomes the exit code of
+	## the process. By default, stdin, stdout and stderr are inherited.
+	##
+	## This only returns if the command could not be started. Stdin bytes and `Piped`
+	## output are not supported, since nothing is left to write or read them, and neither
+	## is a [timeout](Cmd.timeout), since nothing is left to enforce it.
+	##
+	## ```roc
+	## Cmd.new("node").args(args).env("NODE_VERSION", version).exec_replace!()?
+	## ```
+	exec_replace! : Cmd => Try({}, [CmdErr(IOErr)])
+
+	## Run the commands connected by pipes, like `grep error | sort | uniq -c` in a shell,
+	## but without invoking a shell. The stdout of each command is connected to the stdin
+	## of the next one, and the stdout of the last command is captured as a UTF-8 string.
+	## The stderr of all commands is captured as well, in the order of the commands.
+	## Redirections set with [Cmd.stderr], and [Cmd.stdout] of the last command, are respected.
+	##
+	## Only the stdin of the first command is used, and timeouts are ignored.
+	##
+	## The exit status of every command is returned. Like `set -o pipefail` in a shell,
+	## the pipeline fails with `NonZeroExit` if any of the commands fails.
+	##
+	## The stages are a plain list, so they can be built with the usual `List` functions,
+	## for example `List.append` to add a stage at the end.
+	##
+	## ```roc
+	## output = Cmd.pipeline!([
+	##     Cmd.new("grep").arg("error").stdin_str(log),
+	##     Cmd.new("sort"),
+	##     Cmd.new("uniq").arg("-c"),
+	## ])?
+	## ```
+	pipeline! : List(Cmd) => Try(
+		{ statuses : List(Process.ExitStatus), stdout_utf8 : Str, stderr_utf8_lossy : Str },
+		[
+			CmdErr(IOErr),
+			NonZeroExit({ statuses : List(Process.ExitStatus), stdout_utf8_lossy : Str, stderr_utf8_lossy : Str }),
+		],
 	)
 
+	## Start the command without waiting for it to exit.
+	## By default, stdin, stdout and stderr are inherited from the parent process.
+	## Output redirected with `Piped` can be read with [Process.read_line!].
+	## A [timeout](Cmd.timeout) is not supported, stop the process with [Process.kill!] instead.
+	##
+	## ```roc
+	## server = Cmd.new("./server").spawn!()?
+	## run_tests!()?
+	## server.kill!()?
+	## ```
+	spawn! : Cmd => Try(Process, [CmdErr(IOErr)])
+
+	## Start the command without waiting for it to exit, capturing its stdout and stderr.
+	## Read the output as it arrives with [Process.read_line!] or [Process.for_each_line!].
+	## By default, stdin is empty. Like with [Cmd.spawn!], a timeout is not supported.
+	##
+	## ```roc
+	## build = Cmd.new("cargo").arg("build").spawn_piped!()?
+	## line = build.read_line!()?
+	## ```
+	spawn_piped! : Cmd => Try(Process, [CmdErr(IOErr)])
+
+	## Execute a command and call `on_line!` for every line of its stdout and stderr
+	## as soon as it is printed. Returns how the command exited.
+	##
+	## A [timeout](Cmd.timeout) is not supported and fails with `CmdErr`, since the command
+	## runs like with [Cmd.spawn_piped!].
+	##
+	## ```roc
+	## Cmd.new("cargo").arg("build").exec_each_line!(|line| {
+	##     match line {
+	##         Stdout(text) => Stdout.line!(text)
+	##         Stderr(text) => Stderr.line!("build: ${text}")
+	##     }
+	## })?
+	## ```
+	exec_each_line! : Cmd, ([Stderr(Str), Stdout(Str)] => {}) => Try(Process.ExitStatus, [CmdErr(IOErr), ProcessErr(Process.IOErr)])
+	exec_each_line! = |cmd, on_line!| {
+		process = spawn_piped!(cmd)?
+		process.for_each_line!(on_line!)?
+		process.wait!()
+	}
+
 	## Simple helper to execute a command by name with arguments.
 	## Stdin, stdout, and stderr are inherited from the parent process.
 	## Returns Ok if the command exits with code 0.
@@ -119,14 +451,15 @@ Cmd :: {
 	## ```roc
 	## Cmd.exec!("ls", ["-l", "-a"])?
 	## ```
-	exec! : Str, List(Str) => Try({}, [CmdErr(IOErr), ExecFailed({ command : Str, exit_code : I32 })])
+	exec! : Str, List(Str) => Try({}, [CmdErr(IOErr), ExecFailed({ command : Str, status : Process.ExitStatus }), TimedOut])
 	exec! = |program, arguments| {
 		cmd = new(program).args(arguments)
 		result = exec_exit_code!(cmd)
 		match result {
-			Ok(0) => Ok({})
-			Ok(exit_code) => Err(ExecFailed({ command: program, exit_code }))
+			Ok(Exited(0)) => Ok({})
+			Ok(status) => Err(ExecFailed({ command: program, status }))
 			Err(CmdErr(io_err)) => Err(CmdErr(io_err))
+			Err(TimedOut) => Err(TimedOut)
 		}
 	}
 
@@ -137,13 +470,14 @@ Cmd :: {
 	## ```roc
 	## Cmd.new("ls").args(["-l", "-a"]).exec_cmd!()?
 	## ```
-	exec_cmd! : Cmd => Try({}, [CmdErr(IOErr), ExecFailed({ exit_code : I32 })])
+	exec_cmd! : Cmd => Try({}, [CmdErr(IOErr), ExecFailed({ status : Process.ExitStatus }), TimedOut])
 	exec_cmd! = |cmd| {
 		result = exec_exit_code!(cmd)
 		match result {
-			Ok(0) => Ok({})
-			Ok(code) => Err(ExecFailed({ exit_code: code }))
+			Ok(Exited(0)) => Ok({})
+			Ok(status) => Err(ExecFailed({ status }))
 			Err(CmdErr(io_err)) => Err(CmdErr(io_err))
+			Err(TimedOut) => Err(TimedOut)
 		}
 	}
 }
@@ -1,4 +1,30 @@
 Env := [].{
+	## **NotFound** - An entity was not found, often a file.
+	##
+	## **PermissionDenied** - The operation lacked the necessary privileges to complete.
+	##
+	## **BrokenPipe** - The operation failed because a pipe was closed.
+	##
+	## **AlreadyExists** - An entity already exists, often a file.
+	##
+	## **Interrupted** - This operation was interrupted. Interrupted operations can typically be retried.
+	##
+	## **Unsupported** - This operation is unsupported on this platform. This means that the operation can never succeed.
+	##
+	## **OutOfMemory** - An operation could not be completed, because it failed to allocate enough memory.
+	##
+	## **Other** - A custom error that does not fall under any other I/O error kind.
+	IOErr := [
+		NotFound,
+		PermissionDenied,
+		BrokenPipe,
+		AlreadyExists,
+		Interrupted,
+		Unsupported,
+		OutOfMemory,
+		Other(Str),
+	]
+
 	## Reads the given environment variable.
 	##
 	## If the value is invalid Unicode, the invalid parts will be replaced with the
@@ -7,6 +33,16 @@ Env := [].{
 	## Returns an empty string if the variable is not found.
 	var! : Str => Str
 
+	## Gets the command-line arguments as raw bytes, starting with the path of the program.
+	##
+	## Unlike the `List(Str)` passed to `main!`, this preserves arguments that are not valid
+	## UTF-8, such as filenames in a legacy encoding.
+	##
+	## ```roc
+	## paths = Env.args_bytes!({}).drop_first(1)
+	## ```
+	args_bytes! : {} => List(List(U8))
+
 	## Reads the [current working directory](https://en.wikipedia.org/wiki/Working_directory)
 	## from the environment.
 	##
@@ -17,4 +53,98 @@ Env := [].{
 	##
 	## Returns an empty string if the path is unavailable.
 	exe_path! : {} => Str
+
+	## Gets the process ID of the currently-running program.
+	pid! : {} => U32
+
+	## Gets the process ID of the parent of the currently-running program.
+	ppid! : {} => U32
+
+	## Gets the real user ID of the currently-running program.
+	uid! : {} => U32
+
+	## Gets the real group ID of the currently-running program.
+	gid! : {} => U32
+
+	## Gets the effective user ID of the currently-running program.
+	##
+	## This differs from [Env.uid!] when running a setuid executable.
+	euid! : {} => U32
+
+	## Gets the name of the current user from the passwd database.
+	##
+	## Returns an empty string if there is no entry for the real user ID.
+	username! : {} => Str
+
+	## Gets the host name of the machine.
+	##
+	## Returns an empty string if the host name is unavailable.
+	hostname! : {} => Str
+
+	## Gets the home directory of the current user.
+	##
+	## Reads `HOME`, falling back to the user's entry in the passwd database if it is unset, empty or
+	## not an absolute path.
+	##
+	## Returns an empty string if the home directory is unavailable.
+	home_dir! : {} => Str
+
+	## Gets the directory for user-specific configuration files.
+	##
+	## This is `XDG_CONFIG_HOME` if it is set to an absolute path, otherwise `~/.config`.
+	##
+	## Returns an empty string if the directory is unavailable.
+	config_dir! : {} => Str
+
+	## Gets the directory for user-specific non-essential (cached) data.
+	##
+	## This is `XDG_CACHE_HOME` if it is set to an absolute path, otherwise `~/.cache`.
+	##
+	## Returns an empty string if the directory is unavailable.
+	cache_dir! : {} => Str
+
+	## Gets the directory for user-specific data files.
+	##
+	## This is `XDG_DATA_HOME` if it is set to an absolute path, otherwise `~/.local/share`.
+	##
+	## Returns an empty string if the directory is unavailable.
+	data_dir! : {} => Str
+
+	## Gets the directory for user-specific runtime files like sockets and named pipes.
+	##
+	## This is `XDG_RUNTIME_DIR`. The [XDG Base Directory Specification](https://specifications.freedesktop.org/basedir-spec/latest/)
+	## defines no fallback for it.
+	##
+	## Returns an empty string if the variable is unset or not an absolute path.
+	runtime_dir! : {} => Str
+
+	## Gets the directory for temporary files.
+	##
+	## This is `TMPDIR` if it is set and not empty, otherwise `/tmp`.
+	temp_dir! : {} => Str
+
+	## Reads the variables defined in a [dotenv](https://hexdocs.pm/dotenvy/dotenv_file_format.html) file.
+	##
+	## Supports `#` comments, an optional `export` prefix, single-quoted (literal) and
+	## double-quoted (with escapes like `\n`) values. `${VAR}` is replaced with the value of
+	## `VAR` defined earlier in the file or, failing that, in the environment.
+	##
+	## A syntax error is reported as `Other`, including the line number.
+	##
+	## ```roc
+	## vars = Env.read_dotenv!(".env")?
+	## Cmd.new("./server").envs(vars).exec_cmd!()?
+	## ```
+	read_dotenv! : Str => Try(List((Str, Str)), [EnvErr(IOErr)])
+
+	## Reads a [dotenv](https://hexdocs.pm/dotenvy/dotenv_file_format.html) file like
+	## [Env.read_dotenv!] and sets its variables in the environment of the current process.
+	##
+	## Variables that are already set are not overwritten.
+	##
+	## The environment can only be changed safely while the program does nothing in the
+	## background, so call this at the start of `main!`. Once an HTTP request was sent or a
+	## process was spawned, this fails with `Other`. Use [Env.read_dotenv!] and [Cmd.envs]
+	## to pass the variables to commands instead.
+	load_dotenv! : Str => Try({}, [EnvErr(IOErr)])
 }
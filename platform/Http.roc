@@ -1,10 +1,37 @@
 Http := [].{
+	## **NotFound** - An entity was not found, often a file.
+	##
+	## **PermissionDenied** - The operation lacked the necessary privileges to complete.
+	##
+	## **BrokenPipe** - The operation failed because a pipe was closed.
+	##
+	## **AlreadyExists** - An entity already exists, often a file.
+	##
+	## **Interrupted** - This operation was interrupted. Interrupted operations can typically be retried.
+	##
+	## **Unsupported** - This operation is unsupported on this platform. This means that the operation can never succeed.
+	##
+	## **OutOfMemory** - An operation could not be completed, because it failed to allocate enough memory.
+	##
+	## **Other** - A custom error that does not fall under any other I/O error kind.
+	IOErr := [
+		NotFound,
+		PermissionDenied,
+		BrokenPipe,
+		AlreadyExists,
+		Interrupted,
+		Unsupported,
+		OutOfMemory,
+		Other(Str),
+	]
+
 	RequestErr := [
 		Builder,
 		Redirect,
 		Status(U16),
 		Timeout,
 		Request,
+		Connect,
 		Body,
 		Decode,
 		Upgrade,
@@ -29,18 +56,323 @@ Http := [].{
 
 	Header : { name : Str, value : Str }
 
+	## The value of a response header. Header values are usually ASCII, but servers may send
+	## any bytes: values that are valid UTF-8 are `Text`, all others are the raw `Bytes`.
+	HeaderValue := [
+		Bytes(List(U8)),
+		Text(Str),
+	]
+
+	ResponseHeader : { name : Str, value : HeaderValue }
+
+	## Whether to follow redirects, and how many of them at most. Without following, a redirect
+	## is returned as the response. Following more than the maximum fails with `Redirect`.
+	RedirectPolicy := [
+		Follow(U32),
+		None,
+	]
+
+	## A field of a form, see [Http.url_encoded] and [Http.text_part].
+	FormField : { name : Str, value : Str }
+
+	## A part of a `multipart/form-data` body, built with [Http.text_part] or [Http.file_part].
+	##
+	## A `File` part streams the file at `path`. An empty `filename` sends the file name of
+	## `path`, and an empty `content_type` is guessed from its extension.
+	FormPart := [
+		File({ content_type : Str, filename : Str, name : Str, path : Str }),
+		Text(FormField),
+	]
+
+	## The body of a request. `FromFile` streams the file at the given path instead of reading
+	## it into memory first, and sends its size as the `Content-Length`. Failing to open or
+	## read the file fails the request with `FileErr`.
+	##
+	## `Multipart` and `UrlEncoded` are forms, built with [Http.multipart] and
+	## [Http.url_encoded]. They set the `Content-Type` header themselves, replacing any in
+	## the request headers.
+	RequestBody := [
+		Bytes(List(U8)),
+		FromFile(Str),
+		Multipart(List(FormPart)),
+		UrlEncoded(List(FormField)),
+	]
+
+	## A `multipart/form-data` body with the given parts, as sent by HTML forms that upload files.
+	##
+	## ```roc
+	## body = Http.multipart([
+	##     Http.text_part("version", "1.2.0"),
+	##     Http.file_part("artifact", "dist/app.tar.gz", { filename: "", content_type: "application/gzip" }),
+	## ])
+	## ```
+	multipart : List(FormPart) -> RequestBody
+	multipart = |parts| Multipart(parts)
+
+	## A text part named `name`.
+	text_part : Str, Str -> FormPart
+	text_part = |name, value| Text({ name, value })
+
+	## A file part named `name` that streams the file at `path`. Leave `filename` or
+	## `content_type` empty to derive them from `path`.
+	file_part : Str, Str, { filename : Str, content_type : Str } -> FormPart
+	file_part = |name, path, options| File({
+		content_type: options.content_type,
+		filename: options.filename,
+		name,
+		path,
+	})
+
+	## An `application/x-www-form-urlencoded` body with the given fields, in order.
+	##
+	## ```roc
+	## body = Http.url_encoded([{ name: "user", value: "roc" }, { name: "lang", value: "en" }])
+	## ```
+	url_encoded : List(FormField) -> RequestBody
+	url_encoded = |fields| UrlEncoded(fields)
+
+	## A request to send with [Http.send!].
+	##
+	## - `connect_timeout_millis` limits how long connecting to the server may take, 0 for no limit
+	## - `timeout_millis` limits how long the whole request may take, from connecting until the
+	##   body is read, including any redirects, 0 for no limit
+	## - `redirect` decides which redirects are followed, `Follow(10)` is what browsers do
+	##
+	## A request that takes too long fails with `Timeout`.
 	Request : {
 		method : Method,
 		headers : List(Header),
 		uri : Str,
-		body : List(U8),
+		body : RequestBody,
+		connect_timeout_millis : U64,
+		timeout_millis : U64,
+		redirect : RedirectPolicy,
+	}
+
+	## A GET [Request] without headers or body, which may take 30 seconds and follows up to
+	## 10 redirects. Set `uri` to the URL to request.
+	default_request : Request
+	default_request = {
+		method: Get,
+		headers: [],
+		uri: "",
+		body: Bytes([]),
+		connect_timeout_millis: 0,
+		timeout_millis: 30_000,
+		redirect: Follow(10),
 	}
 
+	## A redirect with the given `status`, returned when `url` was requested.
+	Redirect : { status : U16, url : Str }
+
+	## The response to a request.
+	##
+	## - `url` is the URL the response came from, which differs from the requested one if
+	##   redirects were followed
+	## - `redirects` are the redirects that were followed, in order
 	Response : {
 		status : U16,
-		headers : List(Header),
+		headers : List(ResponseHeader),
 		body : List(U8),
+		url : Str,
+		redirects : List(Redirect),
 	}
 
-	send! : Request => Try(Response, [HttpErr(RequestErr)])
+	## Send a request with a client that is shared by all calls to `send!`.
+	## Connections are kept open and reused for later requests to the same host.
+	##
+	## The shared client uses the settings of [Http.default_client_config], including the proxies
+	## of the environment, and cannot be configured. Use [Http.client!] and [Http.send_with!] for
+	## custom headers, proxies or certificates.
+	##
+	## The whole response body is read before this returns. If that fails, for example because
+	## the connection is closed early, the request fails with `Body` or `Timeout`.
+	send! : Request => Try(Response, [FileErr(IOErr), HttpErr(RequestErr)])
+
+	## Handle to a client created with [Http.client!].
+	Client :: { id : U64 }
+
+	## Settings for a client created with [Http.client!].
+	##
+	## - `default_headers` are sent with every request
+	## - `pool_idle_timeout_millis` is how long idle connections are kept open, 0 for the default of 90 seconds
+	## - `user_agent` is sent as the `User-Agent` header, unless it is empty
+	## - `proxy` is the URL of the proxy all requests go through, like `http://proxy:3128` or
+	##   `socks5://proxy:1080`, with an optional `user:password@` to log in. If it is empty, the
+	##   proxies in the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables are used
+	## - `no_proxy` are the hosts that bypass `proxy`: domains, which include their subdomains,
+	##   IP addresses, IP ranges like `10.0.0.0/8`, or `*` for all hosts. It must be empty if
+	##   `proxy` is, use the `NO_PROXY` environment variable for the proxies of the environment
+	## - `ca_bundle_path` is a PEM file with certificates to trust in addition to the system ones
+	## - `client_cert_path` is a PEM file with the client certificate for mutual TLS, followed by its
+	##   private key unless that is in the PEM file at `client_key_path`
+	## - `danger_accept_invalid_certs` accepts any server certificate, even expired or self-signed
+	##   ones for other hosts. Only use it for local test servers, as anyone can then read and
+	##   change the traffic
+	## - `cookie_store` gives the client its own cookie jar, see [Http.cookies!]
+	##
+	## Empty paths are not read. Start from [Http.default_client_config] to only change some of them.
+	ClientConfig : {
+		default_headers : List(Header),
+		pool_idle_timeout_millis : U64,
+		user_agent : Str,
+		proxy : Str,
+		no_proxy : List(Str),
+		ca_bundle_path : Str,
+		client_cert_path : Str,
+		client_key_path : Str,
+		danger_accept_invalid_certs : Bool,
+		cookie_store : Bool,
+	}
+
+	## A [ClientConfig] with no default headers, user agent, cookie jar or custom settings.
+	default_client_config : ClientConfig
+	default_client_config = {
+		default_headers: [],
+		pool_idle_timeout_millis: 0,
+		user_agent: "",
+		proxy: "",
+		no_proxy: [],
+		ca_bundle_path: "",
+		client_cert_path: "",
+		client_key_path: "",
+		danger_accept_invalid_certs: Bool.False,
+		cookie_store: Bool.False,
+	}
+
+	## Create a client with its own settings and connection pool.
+	## Send requests with it using [Http.send_with!].
+	##
+	## Failing to read one of the files in the settings fails with `FileErr`. A file that is
+	## not valid PEM, a proxy that is not a valid URL, or `no_proxy` without a `proxy` fails
+	## with `Builder`.
+	##
+	## ```roc
+	## internal = Http.client!({
+	##     default_headers: [],
+	##     pool_idle_timeout_millis: 0,
+	##     user_agent: "deploy-bot/1.0",
+	##     proxy: "http://proxy.corp.example:3128",
+	##     no_proxy: ["localhost", ".corp.example"],
+	##     ca_bundle_path: "/etc/ssl/corp-ca.pem",
+	##     client_cert_path: "bot.crt",
+	##     client_key_path: "bot.key",
+	##     danger_accept_invalid_certs: Bool.False,
+	##     cookie_store: Bool.False,
+	## })?
+	## response = Http.send_with!(internal, request)?
+	## ```
+	client! : ClientConfig => Try(Client, [FileErr(IOErr), HttpErr(RequestErr)])
+
+	## Send a request with a client created by [Http.client!].
+	send_with! : Client, Request => Try(Response, [FileErr(IOErr), HttpErr(RequestErr)])
+
+	## A cookie in the cookie jar, see [Http.cookies!].
+	##
+	## - `domain` is the host the cookie is sent to, and its subdomains too if `include_subdomains`
+	## - `path` is the path the cookie is sent for, including everything below it
+	## - `expires` is when the cookie expires in seconds since the Unix epoch, 0 for session
+	##   cookies, which are kept until the program exits or the jar is cleared
+	## - `secure` cookies are only sent over HTTPS
+	## - `http_only` cookies are hidden from scripts in browsers, which has no effect here
+	Cookie : {
+		name : Str,
+		value : Str,
+		domain : Str,
+		include_subdomains : Bool,
+		path : Str,
+		expires : U64,
+		secure : Bool,
+		http_only : Bool,
+	}
+
+	## The cookies in the cookie jar of the client that have not expired.
+	##
+	## Only clients created with `cookie_store` keep cookies, each in its own jar. Every
+	## request sent with such a client, including redirects, stores the cookies the server
+	## sets in its jar and is sent with the cookies that apply to it, like in a browser. The
+	## shared client of [Http.send!] and downloads never send or store cookies. A client
+	## without a jar has no cookies.
+	##
+	## A request with its own `Cookie` header is sent without the cookies in the jar, also
+	## after a redirect to another site, which drops that header.
+	##
+	## A cookie whose `Domain` is not the host that set it is ignored, as there is no telling
+	## whether a parent domain like `co.uk` is shared by unrelated sites. So is a `Secure`
+	## cookie set over plain HTTP.
+	cookies! : Client => List(Cookie)
+
+	## Remove all cookies from the cookie jar of the client.
+	clear_cookies! : Client => {}
+
+	## Add the cookies in the file at `path` to the cookie jar of the client, replacing those
+	## with the same name, domain and path. The file is in the Netscape cookie file format
+	## written by [Http.save_cookies!], `curl --cookie-jar` and `wget --save-cookies`.
+	##
+	## A line that is not a cookie fails with `Other`, including the line number, and leaves
+	## the jar unchanged. A client without `cookie_store` fails with `Other` too.
+	load_cookies! : Client, Str => Try({}, [FileErr(IOErr)])
+
+	## Write the cookies in the cookie jar of the client to the file at `path`, so that a
+	## later run can [Http.load_cookies!] them and keep its session. Session cookies are
+	## written too. A client without `cookie_store` fails with `Other`.
+	##
+	## ```roc
+	## # session was created with `cookie_store: Bool.True`
+	## _ = Http.send_with!(session, login_request)?
+	## Http.save_cookies!(session, "cookies.txt")?
+	## ```
+	save_cookies! : Client, Str => Try({}, [FileErr(IOErr)])
+
+	## Send a request with the shared client and stream the response body into the file at
+	## `path`, without holding it in memory. The file is created, or truncated if it exists.
+	## The returned response has an empty body.
+	##
+	## The body is written whatever the status is, so check `response.status` before using
+	## the file. Large downloads likely need a longer `timeout_millis` than the 30 seconds of
+	## [Http.default_request], or 0 for no limit.
+	##
+	## ```roc
+	## response = Http.download!(request, "release.tar.gz")?
+	## ```
+	download! : Request, Str => Try(Response, [FileErr(IOErr), HttpErr(RequestErr)])
+
+	## Like [Http.download!], but calls `on_progress!` every time another part of the body has
+	## been written. `received` is the number of bytes written so far and `total` the length
+	## of the body, or 0 if the server did not send it.
+	##
+	## ```roc
+	## Http.download_with_progress!(request, "release.tar.gz", |{ received, total }| {
+	##     Stderr.write!("\r${received.to_str()} / ${total.to_str()} bytes")
+	## })?
+	## ```
+	download_with_progress! : Request, Str, ({ received : U64, total : U64 } => {}) => Try(Response, [FileErr(IOErr), HttpErr(RequestErr)])
+	download_with_progress! = |request, path, on_progress!| {
+		download = start_download!(request, path)?
+		finish_download!(download, on_progress!)
+	}
+
+	## Handle to a download started with [Http.start_download!].
+	Download :: { id : U64 }
+
+	## Send a request with the shared client and open the file at `path` to stream the response
+	## body into. Write the body with [Http.download_next!] or [Http.finish_download!].
+	start_download! : Request, Str => Try(Download, [FileErr(IOErr), HttpErr(RequestErr)])
+
+	## Write the next part of the body of a download to its file. Returns `Done` with the
+	## response, whose body is empty, once the whole body has been written.
+	download_next! : Download => Try([Done(Response), Progress({ received : U64, total : U64 })], [FileErr(IOErr), HttpErr(RequestErr)])
+
+	## Write the rest of the body of a download to its file, calling `on_progress!` after
+	## every part.
+	finish_download! : Download, ({ received : U64, total : U64 } => {}) => Try(Response, [FileErr(IOErr), HttpErr(RequestErr)])
+	finish_download! = |download, on_progress!| {
+		match download_next!(download)? {
+			Done(response) => Ok(response)
+			Progress(progress) => {
+				on_progress!(progress)
+				finish_download!(download, on_progress!)
+			}
+		}
+	}
 }
@@ -0,0 +1,96 @@
+## A handle to a child process started with [Cmd.spawn!].
+##
+## The handle stays valid until the process has been waited for with [Process.wait!],
+## [Process.try_wait!] or [Process.kill!].
+Process :: {
+	id : U64,
+	pid : U32,
+}.{
+	## **NotFound** - An entity was not found, often a file.
+	##
+	## **PermissionDenied** - The operation lacked the necessary privileges to complete.
+	##
+	## **BrokenPipe** - The operation failed because a pipe was closed.
+	##
+	## **AlreadyExists** - An entity already exists, often a file.
+	##
+	## **Interrupted** - This operation was interrupted. Interrupted operations can typically be retried.
+	##
+	## **Unsupported** - This operation is unsupported on this platform. This means that the operation can never succeed.
+	##
+	## **OutOfMemory** - An operation could not be completed, because it failed to allocate enough memory.
+	##
+	## **Other** - A custom error that does not fall under any other I/O error kind.
+	IOErr := [
+		NotFound,
+		PermissionDenied,
+		BrokenPipe,
+		AlreadyExists,
+		Interrupted,
+		Unsupported,
+		OutOfMemory,
+		Other(Str),
+	]
+
+	## How a process ended: it either exited with an exit code, or it was terminated
+	## by a signal (e.g. `11` for `SIGSEGV`), possibly leaving a core dump behind.
+	ExitStatus : [Exited(I32), Signaled({ core_dumped : Bool, signal : I32 })]
+
+	## The operating system's ID of the process.
+	pid : Process -> U32
+	pid = |process| process.pid
+
+	## Wait for the next line of the captured stdout or stderr of a process. Output is captured
+	## by default with [Cmd.spawn_piped!], and when it is redirected with `Piped` for both
+	## [Cmd.spawn_piped!] and [Cmd.spawn!].
+	## The trailing `\n` or `\r\n` is removed and invalid UTF-8 is replaced with the Unicode
+	## replacement character.
+	##
+	## Returns `End` once the captured streams are closed, usually because the process exited.
+	## If neither stream is captured this returns `End` right away.
+	##
+	## Lines that have not been read are discarded when the process is waited for.
+	read_line! : Process => Try([End, Stderr(Str), Stdout(Str)], [ProcessErr(IOErr)])
+
+	## Call `on_line!` for every line of the captured stdout and stderr of a process, until
+	## the captured streams are closed. See [Process.read_line!] for which output is captured.
+	for_each_line! : Process, ([Stderr(Str), Stdout(Str)] => {}) => Try({}, [ProcessErr(IOErr)])
+	for_each_line! = |process, on_line!| {
+		match read_line!(process)? {
+			End => Ok({})
+			Stderr(line) => {
+				on_line!(Stderr(line))
+				for_each_line!(process, on_line!)
+			}
+			Stdout(line) => {
+				on_line!(Stdout(line))
+				for_each_line!(process, on_line!)
+			}
+		}
+	}
+
+	## Wait for the process to exit and return how it exited.
+	##
+	## ```roc
+	## status = Cmd.new("sleep").arg("1").spawn!()?.wait!()?
+	## ```
+	wait! : Process => Try(ExitStatus, [ProcessErr(IOErr)])
+
+	## Check whether the process has exited, without blocking.
+	##
+	## ```roc
+	## match server.try_wait!()? {
+	##     Running => Stdout.line!("still up")
+	##     Finished(status) => Stdout.line!("finished: ${Str.inspect(status)}")
+	## }
+	## ```
+	try_wait! : Process => Try([Finished(ExitStatus), Running], [ProcessErr(IOErr)])
+
+	## Forcefully kill the process (with `SIGKILL`) and wait for it to exit.
+	kill! : Process => Try({}, [ProcessErr(IOErr)])
+
+	## Send a signal to the process, e.g. `15` (`SIGTERM`) to ask it to shut down.
+	##
+	## Use [Process.wait!] afterwards to wait for it to exit.
+	signal! : Process, I32 => Try({}, [ProcessErr(IOErr)])
+}
@@ -9,6 +9,7 @@ platform ""
 		File,
 		Http,
 		Path,
+		Process,
 		Random,
 		Sleep,
 		Stderr,
@@ -33,6 +34,7 @@ import Env
 import File
 import Http
 import Path
+import Process
 import Random
 import Sleep
 import Stderr
@@ -0,0 +1,904 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, PipeReader, PipeWriter, Read, Write},
+    mem::{ManuallyDrop, MaybeUninit},
+    os::{
+        fd::{AsFd, OwnedFd},
+        unix::process::CommandExt,
+    },
+    process::{Child, ChildStdin, ExitStatus, Stdio},
+    time::{Duration, Instant},
+};
+
+use roc_platform_builder::roc_std_new::{RocList, RocRefcounted, RocStr, roc_refcounted_noop_impl};
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[repr(u8)]
+pub enum StdinTag {
+    Bytes = 0,
+    Default = 1,
+    File = 2,
+    Inherit = 3,
+    Null = 4,
+}
+
+impl core::fmt::Debug for StdinTag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bytes => f.write_str("StdinTag::Bytes"),
+            Self::Default => f.write_str("StdinTag::Default"),
+            Self::File => f.write_str("StdinTag::File"),
+            Self::Inherit => f.write_str("StdinTag::Inherit"),
+            Self::Null => f.write_str("StdinTag::Null"),
+        }
+    }
+}
+
+roc_refcounted_noop_impl!(StdinTag);
+
+#[repr(C)]
+pub union StdinPayload {
+    bytes: ManuallyDrop<RocList<u8>>,
+    path: ManuallyDrop<RocStr>,
+}
+
+/// `[Bytes(List(U8)), Default, File(Str), Inherit, Null]`
+#[repr(C)]
+pub struct Stdin {
+    payload: MaybeUninit<StdinPayload>,
+    pub tag: StdinTag,
+}
+
+impl Stdin {
+    pub fn bytes(&self) -> Option<&RocList<u8>> {
+        if matches!(self.tag, StdinTag::Bytes) {
+            Some(unsafe { &self.payload.assume_init_ref().bytes })
+        } else {
+            None
+        }
+    }
+
+    pub fn path(&self) -> Option<&RocStr> {
+        if matches!(self.tag, StdinTag::File) {
+            Some(unsafe { &self.payload.assume_init_ref().path })
+        } else {
+            None
+        }
+    }
+}
+
+impl Clone for Stdin {
+    fn clone(&self) -> Self {
+        let payload = match (self.bytes(), self.path()) {
+            (Some(bytes), _) => MaybeUninit::new(StdinPayload {
+                bytes: ManuallyDrop::new(bytes.clone()),
+            }),
+            (_, Some(path)) => MaybeUninit::new(StdinPayload {
+                path: ManuallyDrop::new(path.clone()),
+            }),
+            (None, None) => MaybeUninit::zeroed(),
+        };
+        Self {
+            payload,
+            tag: self.tag,
+        }
+    }
+}
+
+impl core::fmt::Debug for Stdin {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match (self.bytes(), self.path()) {
+            (Some(bytes), _) => write!(f, "StdinTag::Bytes({bytes:?})"),
+            (_, Some(path)) => write!(f, "StdinTag::File({path:?})"),
+            (None, None) => write!(f, "{:?}", self.tag),
+        }
+    }
+}
+
+impl RocRefcounted for Stdin {
+    fn inc(&mut self) {
+        match self.tag {
+            StdinTag::Bytes => unsafe { self.payload.assume_init_mut().bytes.inc() },
+            StdinTag::File => unsafe { self.payload.assume_init_mut().path.inc() },
+            _ => {}
+        }
+    }
+    fn dec(&mut self) {
+        match self.tag {
+            StdinTag::Bytes => unsafe { self.payload.assume_init_mut().bytes.dec() },
+            StdinTag::File => unsafe { self.payload.assume_init_mut().path.dec() },
+            _ => {}
+        }
+    }
+    fn is_refcounted() -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[repr(u8)]
+pub enum OutputRedirectTag {
+    Append = 0,
+    Default = 1,
+    Inherit = 2,
+    Null = 3,
+    Piped = 4,
+    ToStdout = 5,
+    Truncate = 6,
+}
+
+impl core::fmt::Debug for OutputRedirectTag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Append => f.write_str("OutputRedirectTag::Append"),
+            Self::Default => f.write_str("OutputRedirectTag::Default"),
+            Self::Inherit => f.write_str("OutputRedirectTag::Inherit"),
+            Self::Null => f.write_str("OutputRedirectTag::Null"),
+            Self::Piped => f.write_str("OutputRedirectTag::Piped"),
+            Self::ToStdout => f.write_str("OutputRedirectTag::ToStdout"),
+            Self::Truncate => f.write_str("OutputRedirectTag::Truncate"),
+        }
+    }
+}
+
+roc_refcounted_noop_impl!(OutputRedirectTag);
+
+/// `[Append(Str), Default, Inherit, Null, Piped, ToStdout, Truncate(Str)]`
+#[repr(C)]
+pub struct OutputRedirect {
+    path: MaybeUninit<RocStr>,
+    pub tag: OutputRedirectTag,
+}
+
+impl OutputRedirect {
+    pub fn path(&self) -> Option<&RocStr> {
+        if matches!(
+            self.tag,
+            OutputRedirectTag::Append | OutputRedirectTag::Truncate
+        ) {
+            Some(unsafe { self.path.assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Opens the target of the redirect. If it is `Piped`, or `Default` when
+    /// output is captured by default, the read end of the pipe is stored in
+    /// `reader`.
+    fn sink(&self, defaults: Defaults, reader: &mut Option<PipeReader>) -> io::Result<Sink> {
+        let sink = match self.tag {
+            OutputRedirectTag::Append => {
+                let path = self.path().expect("is append").as_str();
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                Sink::Fd(file.into())
+            }
+            OutputRedirectTag::Default if defaults == Defaults::Inherit => Sink::Inherit,
+            OutputRedirectTag::Default | OutputRedirectTag::Piped => {
+                let (pipe_reader, pipe_writer) = io::pipe()?;
+                *reader = Some(pipe_reader);
+                Sink::Fd(pipe_writer.into())
+            }
+            OutputRedirectTag::Inherit => Sink::Inherit,
+            OutputRedirectTag::Null => Sink::Null,
+            OutputRedirectTag::ToStdout => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "only stderr can be redirected to stdout",
+                ));
+            }
+            OutputRedirectTag::Truncate => {
+                let path = self.path().expect("is truncate").as_str();
+                Sink::Fd(File::create(path)?.into())
+            }
+        };
+        Ok(sink)
+    }
+}
+
+impl Clone for OutputRedirect {
+    fn clone(&self) -> Self {
+        match self.path() {
+            Some(path) => Self {
+                path: MaybeUninit::new(path.clone()),
+                tag: self.tag,
+            },
+            None => Self {
+                path: MaybeUninit::zeroed(),
+                tag: self.tag,
+            },
+        }
+    }
+}
+
+impl core::fmt::Debug for OutputRedirect {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.path() {
+            Some(path) => write!(f, "{:?}({path:?})", self.tag),
+            None => write!(f, "{:?}", self.tag),
+        }
+    }
+}
+
+impl RocRefcounted for OutputRedirect {
+    fn inc(&mut self) {
+        if self.path().is_some() {
+            unsafe { self.path.assume_init_mut().inc() };
+        }
+    }
+    fn dec(&mut self) {
+        if self.path().is_some() {
+            unsafe { self.path.assume_init_mut().dec() };
+        }
+    }
+    fn is_refcounted() -> bool {
+        true
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub args: RocList<RocStr>,
+    /// empty means the working directory is inherited from the parent process
+    pub cwd: RocStr,
+    /// flattened key/value pairs: `[key0, value0, key1, value1, ...]`
+    pub envs: RocList<RocStr>,
+    pub program: RocStr,
+    pub stderr: OutputRedirect,
+    pub stdin: Stdin,
+    pub stdout: OutputRedirect,
+    /// 0 means no timeout
+    pub timeout_millis: u64,
+    pub clear_envs: bool,
+}
+
+/// What `Default` means for the stdio of a command.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Defaults {
+    /// stdin, stdout and stderr are inherited
+    Inherit,
+    /// stdin is empty, stdout and stderr are captured
+    Capture,
+}
+
+/// Where an output stream of a child goes.
+enum Sink {
+    Inherit,
+    Null,
+    Fd(OwnedFd),
+}
+
+impl From<Sink> for Stdio {
+    fn from(sink: Sink) -> Self {
+        match sink {
+            Sink::Inherit => Stdio::inherit(),
+            Sink::Null => Stdio::null(),
+            Sink::Fd(fd) => Stdio::from(fd),
+        }
+    }
+}
+
+/// The read ends of the output streams of a child that are captured.
+#[derive(Default)]
+pub struct Captured {
+    pub stdout: Option<PipeReader>,
+    pub stderr: Option<PipeReader>,
+}
+
+/// How long a command that exceeded its timeout gets to exit after `SIGTERM`
+/// before it is killed with `SIGKILL`.
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The result of running a command to completion.
+pub struct Output {
+    pub status: ExitStatus,
+    /// empty unless the output was captured
+    pub stdout: Vec<u8>,
+    /// empty unless the output was captured
+    pub stderr: Vec<u8>,
+    /// whether the command was killed because it exceeded its timeout
+    pub timed_out: bool,
+}
+
+impl Command {
+    /// Runs the command and waits for it to exit. By default, stdin, stdout
+    /// and stderr are inherited.
+    pub fn status(&self) -> io::Result<Output> {
+        self.run(Defaults::Inherit)
+    }
+
+    /// Runs the command and collects its stdout and stderr. By default, stdin
+    /// is empty and stdout and stderr are captured.
+    pub fn output(&self) -> io::Result<Output> {
+        self.run(Defaults::Capture)
+    }
+
+    /// Starts the command without waiting for it. By default, stdin, stdout
+    /// and stderr are inherited.
+    ///
+    /// The timeout does not apply to spawned commands.
+    pub fn spawn(&self) -> io::Result<(Child, Captured)> {
+        self.spawn_with_input(Defaults::Inherit)
+    }
+
+    /// Starts the command without waiting for it. By default, stdin is empty
+    /// and stdout and stderr are captured.
+    pub fn spawn_piped(&self) -> io::Result<(Child, Captured)> {
+        self.spawn_with_input(Defaults::Capture)
+    }
+
+    /// A timeout is rejected, as nothing would be waiting for the child to
+    /// enforce it.
+    fn spawn_with_input(&self, defaults: Defaults) -> io::Result<(Child, Captured)> {
+        if self.timeout().is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a timeout is not supported when spawning a process",
+            ));
+        }
+        let (mut child, captured) = self.spawn_configured(defaults, None, None)?;
+        if let Some(stdin) = child.stdin.take() {
+            let bytes = self
+                .stdin
+                .bytes()
+                .expect("stdin is piped")
+                .as_slice()
+                .to_vec();
+            // Nobody waits for this thread, so a failed write is only
+            // noticed by the child seeing its input end early.
+            std::thread::spawn(move || write_stdin(stdin, &bytes));
+        }
+        Ok((child, captured))
+    }
+
+    /// Spawns the command with its stdio set up by [`Command::configure`].
+    ///
+    /// If the command has stdin bytes, the caller has to write them to the
+    /// child's stdin.
+    fn spawn_configured(
+        &self,
+        defaults: Defaults,
+        stdin: Option<PipeReader>,
+        stdout: Option<PipeWriter>,
+    ) -> io::Result<(Child, Captured)> {
+        let (mut command, captured) = self.configure(defaults, stdin, stdout)?;
+        let child = command.spawn()?;
+        // Closes our copies of the write ends of the pipes, so that reading
+        // the captured output ends when the child exits.
+        drop(command);
+
+        Ok((child, captured))
+    }
+
+    /// Sets up the stdio of the command according to its redirects and
+    /// `defaults`. `stdin` and `stdout` take precedence over the command's own
+    /// settings; they connect the stages of a pipeline.
+    fn configure(
+        &self,
+        defaults: Defaults,
+        stdin: Option<PipeReader>,
+        stdout: Option<PipeWriter>,
+    ) -> io::Result<(std::process::Command, Captured)> {
+        let mut captured = Captured::default();
+
+        let stdin = match stdin {
+            Some(reader) => Stdio::from(reader),
+            None => match self.stdin.tag {
+                StdinTag::Bytes => Stdio::piped(),
+                StdinTag::Default if defaults == Defaults::Capture => Stdio::null(),
+                StdinTag::Default | StdinTag::Inherit => Stdio::inherit(),
+                StdinTag::File => {
+                    Stdio::from(File::open(self.stdin.path().expect("is file").as_str())?)
+                }
+                StdinTag::Null => Stdio::null(),
+            },
+        };
+        let stdout = match stdout {
+            Some(writer) => Sink::Fd(writer.into()),
+            None => self.stdout.sink(defaults, &mut captured.stdout)?,
+        };
+        let stderr = match self.stderr.tag {
+            OutputRedirectTag::ToStdout => match &stdout {
+                Sink::Inherit => Sink::Fd(io::stdout().as_fd().try_clone_to_owned()?),
+                Sink::Null => Sink::Null,
+                Sink::Fd(fd) => Sink::Fd(fd.try_clone()?),
+            },
+            _ => self.stderr.sink(defaults, &mut captured.stderr)?,
+        };
+
+        let mut command = std::process::Command::from(self);
+        command.stdin(stdin).stdout(stdout).stderr(stderr);
+
+        Ok((command, captured))
+    }
+
+    /// Replaces the current process with the command, like `exec` in shells.
+    /// By default, stdin, stdout and stderr are inherited.
+    ///
+    /// Only returns if that fails. Stdin bytes and piped output are rejected,
+    /// as nobody would be left to write or read them, and so is a timeout, as
+    /// nobody would be left to enforce it.
+    pub fn exec_replace(&self) -> io::Error {
+        let piped = |redirect: &OutputRedirect| redirect.tag == OutputRedirectTag::Piped;
+        if self.stdin.tag == StdinTag::Bytes || piped(&self.stdout) || piped(&self.stderr) {
+            return io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "stdin bytes and piped output are not supported when replacing the process",
+            );
+        }
+        if self.timeout().is_some() {
+            return io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a timeout is not supported when replacing the process",
+            );
+        }
+
+        let mut command = match self.configure(Defaults::Inherit, None, None) {
+            Ok((command, _)) => command,
+            Err(e) => return e,
+        };
+        // Anything still buffered would be lost with the current process.
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
+        command.exec()
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        (self.timeout_millis > 0).then(|| Duration::from_millis(self.timeout_millis))
+    }
+
+    /// Stdin is written and stdout/stderr are read on separate threads while
+    /// we wait for the child, so a child that fills its output pipes before
+    /// reading all of its input cannot deadlock us.
+    ///
+    /// With a timeout, the child gets its own process group, so that the
+    /// whole group can be stopped. Otherwise processes it started in the
+    /// background would survive and keep the output pipes open.
+    fn run(&self, defaults: Defaults) -> io::Result<Output> {
+        let (mut command, captured) = self.configure(defaults, None, None)?;
+        if self.timeout().is_some() {
+            command.process_group(0);
+        }
+        let mut child = command.spawn()?;
+        drop(command);
+
+        let stdin = child.stdin.take();
+        std::thread::scope(|s| {
+            let writer = stdin.map(|stdin| {
+                let bytes = self.stdin.bytes().expect("stdin is piped").as_slice();
+                s.spawn(move || write_stdin(stdin, bytes))
+            });
+            let stdout = captured
+                .stdout
+                .map(|stdout| s.spawn(move || read_all(stdout)));
+            let stderr = captured
+                .stderr
+                .map(|stderr| s.spawn(move || read_all(stderr)));
+
+            let (status, timed_out) = wait_timeout(&mut child, self.timeout())?;
+
+            if let Some(writer) = writer {
+                writer.join().expect("stdin writer panicked")?;
+            }
+            let stdout = match stdout {
+                Some(reader) => reader.join().expect("stdout reader panicked")?,
+                None => Vec::new(),
+            };
+            let stderr = match stderr {
+                Some(reader) => reader.join().expect("stderr reader panicked")?,
+                None => Vec::new(),
+            };
+
+            Ok(Output {
+                status,
+                stdout,
+                stderr,
+                timed_out,
+            })
+        })
+    }
+}
+
+/// The result of running a pipeline to completion.
+pub struct PipelineOutput {
+    /// the exit status of every stage, in order
+    pub statuses: Vec<ExitStatus>,
+    /// stdout of the last stage, if it is captured
+    pub stdout: Vec<u8>,
+    /// stderr of all stages whose stderr is captured, in stage order
+    pub stderr: Vec<u8>,
+}
+
+impl PipelineOutput {
+    /// Whether every stage succeeded, like `set -o pipefail` in shells.
+    pub fn success(&self) -> bool {
+        self.statuses.iter().all(ExitStatus::success)
+    }
+}
+
+/// Runs the commands connected by OS pipes, stdout of each to stdin of the
+/// next. By default, the stdin of the first stage is empty, and the stdout of
+/// the last stage and the stderr of all stages are captured.
+///
+/// The stdin of all but the first and the stdout of all but the last command
+/// are ignored, as are timeouts.
+pub fn pipeline(cmds: &[Command]) -> io::Result<PipelineOutput> {
+    let Some(first) = cmds.first() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "a pipeline needs at least one command",
+        ));
+    };
+
+    let mut children: Vec<Child> = Vec::with_capacity(cmds.len());
+    let mut stderrs = Vec::with_capacity(cmds.len());
+    let mut stdout = None;
+    let mut prev_stdout: Option<PipeReader> = None;
+    for (i, cmd) in cmds.iter().enumerate() {
+        let is_last = i + 1 == cmds.len();
+        let spawned = if is_last {
+            cmd.spawn_configured(Defaults::Capture, prev_stdout.take(), None)
+        } else {
+            io::pipe().and_then(|(reader, writer)| {
+                let spawned =
+                    cmd.spawn_configured(Defaults::Capture, prev_stdout.take(), Some(writer));
+                prev_stdout = Some(reader);
+                spawned
+            })
+        };
+
+        match spawned {
+            Ok((child, captured)) => {
+                children.push(child);
+                stderrs.push(captured.stderr);
+                if is_last {
+                    stdout = captured.stdout;
+                }
+            }
+            Err(e) => {
+                for mut child in children {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    let stdin = children[0].stdin.take();
+    std::thread::scope(|s| {
+        let writer = stdin.map(|stdin| {
+            let bytes = first.stdin.bytes().expect("stdin is piped").as_slice();
+            s.spawn(move || write_stdin(stdin, bytes))
+        });
+        let stdout = stdout.map(|stdout| s.spawn(move || read_all(stdout)));
+        let stderrs: Vec<_> = stderrs
+            .into_iter()
+            .flatten()
+            .map(|stderr| s.spawn(move || read_all(stderr)))
+            .collect();
+
+        let statuses = children
+            .iter_mut()
+            .map(Child::wait)
+            .collect::<io::Result<Vec<_>>>()?;
+
+        if let Some(writer) = writer {
+            writer.join().expect("stdin writer panicked")?;
+        }
+        let stdout = match stdout {
+            Some(reader) => reader.join().expect("stdout reader panicked")?,
+            None => Vec::new(),
+        };
+        let mut stderr = Vec::new();
+        for reader in stderrs {
+            stderr.extend(reader.join().expect("stderr reader panicked")?);
+        }
+
+        Ok(PipelineOutput {
+            statuses,
+            stdout,
+            stderr,
+        })
+    })
+}
+
+/// Writes the bytes to the stdin of a child. Must be called on a thread of
+/// its own, as it blocks `SIGPIPE` for the calling thread.
+fn write_stdin(mut stdin: ChildStdin, bytes: &[u8]) -> io::Result<()> {
+    // The host is started from C, so `SIGPIPE` is not ignored as in Rust
+    // programs, and writing to a child that exited would kill the whole
+    // program. With the signal blocked, the write fails with `BrokenPipe`
+    // instead, and the pending signal is discarded when the thread exits.
+    unsafe {
+        let mut set = MaybeUninit::<libc::sigset_t>::uninit();
+        libc::sigemptyset(set.as_mut_ptr());
+        libc::sigaddset(set.as_mut_ptr(), libc::SIGPIPE);
+        libc::pthread_sigmask(libc::SIG_BLOCK, set.as_ptr(), std::ptr::null_mut());
+    }
+
+    match stdin.write_all(bytes) {
+        // The child exited or closed stdin without reading all of it.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn read_all(mut reader: impl Read) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Waits for the child to exit. If it is still running after `timeout`, its
+/// process group is sent `SIGTERM` and, if the child ignores that, `SIGKILL`.
+/// The child must be the leader of its process group.
+///
+/// Returns whether the child was stopped because of the timeout.
+pub fn wait_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> io::Result<(ExitStatus, bool)> {
+    let Some(timeout) = timeout else {
+        return Ok((child.wait()?, false));
+    };
+
+    if let Some(status) = poll_until(child, Instant::now() + timeout)? {
+        return Ok((status, false));
+    }
+
+    kill_group(child, libc::SIGTERM)?;
+    let status = match poll_until(child, Instant::now() + KILL_GRACE_PERIOD)? {
+        Some(status) => status,
+        None => {
+            kill_group(child, libc::SIGKILL)?;
+            child.wait()?
+        }
+    };
+    // Whatever is left of the group would keep the output pipes open.
+    let _ = kill_group(child, libc::SIGKILL);
+
+    Ok((status, true))
+}
+
+fn kill_group(child: &Child, signal: libc::c_int) -> io::Result<()> {
+    if unsafe { libc::killpg(child.id() as libc::pid_t, signal) } == -1 {
+        let e = io::Error::last_os_error();
+        // The group is already gone.
+        if e.raw_os_error() != Some(libc::ESRCH) {
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+fn poll_until(child: &mut Child, deadline: Instant) -> io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+impl From<&Command> for std::process::Command {
+    fn from(cmd: &Command) -> Self {
+        let mut command = std::process::Command::new(cmd.program.as_str());
+        command.args(cmd.args.iter().map(|arg| arg.as_str()));
+
+        if cmd.clear_envs {
+            command.env_clear();
+        }
+        for pair in cmd.envs.as_slice().chunks_exact(2) {
+            command.env(pair[0].as_str(), pair[1].as_str());
+        }
+
+        if !cmd.cwd.as_str().is_empty() {
+            command.current_dir(cmd.cwd.as_str());
+        }
+
+        command
+    }
+}
+
+impl RocRefcounted for Command {
+    fn inc(&mut self) {
+        self.args.inc();
+        self.cwd.inc();
+        self.envs.inc();
+        self.program.inc();
+        self.stderr.inc();
+        self.stdin.inc();
+        self.stdout.inc();
+    }
+    fn dec(&mut self) {
+        self.args.dec();
+        self.cwd.dec();
+        self.envs.dec();
+        self.program.dec();
+        self.stderr.dec();
+        self.stdin.dec();
+        self.stdout.dec();
+    }
+    fn is_refcounted() -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use roc_platform_builder::roc_std_new::RocOps;
+
+    use super::*;
+
+    /// `sh -c script`, with `stdin` written to it if given.
+    pub(crate) fn sh(
+        script: &str,
+        timeout_millis: u64,
+        stdin: Option<&[u8]>,
+        ops: &RocOps,
+    ) -> Command {
+        let args = [RocStr::from_str("-c", ops), RocStr::from_str(script, ops)];
+        let redirect = || OutputRedirect {
+            path: MaybeUninit::zeroed(),
+            tag: OutputRedirectTag::Default,
+        };
+        let stdin = match stdin {
+            Some(bytes) => Stdin {
+                payload: MaybeUninit::new(StdinPayload {
+                    bytes: ManuallyDrop::new(RocList::from_slice(bytes, ops)),
+                }),
+                tag: StdinTag::Bytes,
+            },
+            None => Stdin {
+                payload: MaybeUninit::zeroed(),
+                tag: StdinTag::Default,
+            },
+        };
+        Command {
+            args: RocList::from_slice(&args, ops),
+            cwd: RocStr::empty(),
+            envs: RocList::empty(),
+            program: RocStr::from_str("sh", ops),
+            stderr: redirect(),
+            stdin,
+            stdout: redirect(),
+            timeout_millis,
+            clear_envs: false,
+        }
+    }
+
+    #[test]
+    fn timeout_stops_background_processes() {
+        let ops = RocOps::mock();
+        let started = Instant::now();
+        let output = sh("sleep 100 & sleep 100", 200, None, &ops)
+            .output()
+            .unwrap();
+
+        assert!(output.timed_out);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn timeout_stops_processes_ignoring_sigterm() {
+        let ops = RocOps::mock();
+        let script = "trap '' TERM; sleep 100 & echo started; wait";
+        let output = sh(script, 200, None, &ops).output().unwrap();
+
+        assert!(output.timed_out);
+        assert_eq!(output.stdout, b"started\n");
+    }
+
+    #[test]
+    fn unread_stdin() {
+        // Like the host, which does not ignore `SIGPIPE`, unlike the test
+        // harness.
+        let previous = unsafe { libc::signal(libc::SIGPIPE, libc::SIG_DFL) };
+        let ops = RocOps::mock();
+        let input = vec![b'x'; 4 << 20];
+        let output = sh("head -c1", 0, Some(&input), &ops).output();
+        unsafe { libc::signal(libc::SIGPIPE, previous) };
+
+        let output = output.unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"x");
+    }
+
+    #[test]
+    fn pipeline_connects_stages() {
+        let ops = RocOps::mock();
+        let output = pipeline(&[
+            sh("cat; printf 'b\\na\\n'", 0, Some(b"b\n"), &ops),
+            sh("sort", 0, None, &ops),
+            sh("uniq -c | tr -s ' '", 0, None, &ops),
+        ])
+        .unwrap();
+
+        assert!(output.success());
+        assert_eq!(output.stdout, b" 1 a\n 2 b\n");
+    }
+
+    #[test]
+    fn pipeline_statuses() {
+        let ops = RocOps::mock();
+        for (scripts, codes) in [
+            (
+                ["echo one >&2; exit 3", "cat >/dev/null; echo two >&2"],
+                [3, 0],
+            ),
+            (
+                ["echo one >&2", "cat >/dev/null; echo two >&2; exit 4"],
+                [0, 4],
+            ),
+            (["echo one >&2", "cat >/dev/null; echo two >&2"], [0, 0]),
+        ] {
+            let cmds = scripts.map(|script| sh(script, 0, None, &ops));
+            let output = pipeline(&cmds).unwrap();
+
+            let actual: Vec<_> = output.statuses.iter().map(ExitStatus::code).collect();
+            assert_eq!(actual, codes.map(Some), "{scripts:?}");
+            assert_eq!(output.success(), codes == [0, 0], "{scripts:?}");
+            // Even of stages that failed, in stage order.
+            assert_eq!(output.stderr, b"one\ntwo\n", "{scripts:?}");
+        }
+    }
+
+    #[test]
+    fn pipeline_spawn_failure() {
+        let children = || std::fs::read_to_string("/proc/thread-self/children").unwrap();
+        let ops = RocOps::mock();
+        let mut missing = sh("", 0, None, &ops);
+        missing.program = RocStr::from_str("/nonexistent/basic-cli-test", &ops);
+        let cmds = [
+            sh("sleep 100", 0, None, &ops),
+            sh("sleep 100", 0, None, &ops),
+            missing,
+        ];
+
+        let started = Instant::now();
+        let e = pipeline(&cmds)
+            .err()
+            .expect("the last stage does not exist");
+
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        // The stages that were started are neither running nor zombies.
+        assert_eq!(children().trim(), "");
+    }
+
+    #[test]
+    fn exec_replace_rejects_timeout() {
+        let ops = RocOps::mock();
+        let e = sh("echo replaced", 1000, None, &ops).exec_replace();
+
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn spawn_rejects_timeout() {
+        let ops = RocOps::mock();
+        let cmd = sh("echo spawned", 1000, None, &ops);
+
+        for spawned in [cmd.spawn(), cmd.spawn_piped()] {
+            let e = spawned.err().expect("the timeout is rejected");
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn no_timeout() {
+        let ops = RocOps::mock();
+        let output = sh("echo done", 0, None, &ops).output().unwrap();
+
+        assert!(!output.timed_out);
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"done\n");
+    }
+}
@@ -0,0 +1,815 @@
+use std::{
+    fmt::Write,
+    io,
+    net::Ipv4Addr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{
+    Url,
+    header::{HeaderMap, HeaderValue, SET_COOKIE},
+};
+use roc_platform_builder::roc_std_new::{RocList, RocOps, RocRefcounted, RocStr};
+
+/// `{ domain : Str, expires : U64, http_only : Bool, include_subdomains : Bool, name : Str, path : Str, secure : Bool, value : Str }`
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct Cookie {
+    domain: RocStr,
+    /// seconds since the Unix epoch, 0 for session cookies
+    expires: u64,
+    name: RocStr,
+    path: RocStr,
+    value: RocStr,
+    http_only: bool,
+    include_subdomains: bool,
+    secure: bool,
+}
+
+impl Cookie {
+    fn new(cookie: &StoredCookie, ops: &RocOps) -> Self {
+        Self {
+            domain: RocStr::from_str(&cookie.domain, ops),
+            expires: cookie.expires.unwrap_or(0),
+            name: RocStr::from_str(&cookie.name, ops),
+            path: RocStr::from_str(&cookie.path, ops),
+            value: RocStr::from_str(&cookie.value, ops),
+            http_only: cookie.http_only,
+            include_subdomains: cookie.include_subdomains,
+            secure: cookie.secure,
+        }
+    }
+}
+
+impl RocRefcounted for Cookie {
+    fn inc(&mut self) {
+        self.domain.inc();
+        self.name.inc();
+        self.path.inc();
+        self.value.inc();
+    }
+    fn dec(&mut self) {
+        self.domain.dec();
+        self.name.dec();
+        self.path.dec();
+        self.value.dec();
+    }
+    fn is_refcounted() -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    /// lowercase, without a leading dot
+    domain: String,
+    /// whether the cookie is also sent to subdomains of `domain`, which is
+    /// the case if it was set with a `Domain` attribute
+    include_subdomains: bool,
+    path: String,
+    secure: bool,
+    http_only: bool,
+    /// seconds since the Unix epoch, `None` for session cookies
+    expires: Option<u64>,
+}
+
+impl StoredCookie {
+    /// Parses a `Set-Cookie` header received in response to a request for
+    /// `url`, as described in RFC 6265.
+    ///
+    /// Returns `None` for cookies the browser would ignore, like those with a
+    /// `Domain` the host of `url` does not belong to. A `Domain` other than
+    /// the host itself is also ignored for IP addresses, and if it is a
+    /// single label like `com`, which would share the cookie with every site
+    /// under it.
+    fn parse(set_cookie: &str, url: &Url, now: u64) -> Option<Self> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let mut attributes = set_cookie.split(';');
+        let (name, value) = attributes.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut domain = None;
+        let mut path = None;
+        let mut secure = false;
+        let mut http_only = false;
+        let mut expires = None;
+        let mut max_age = None;
+        for attribute in attributes {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "expires" => expires = parse_date(value).or(expires),
+                "max-age" => {
+                    // Anything but a number is ignored, and a number that is
+                    // not positive expires the cookie right away.
+                    if let Ok(seconds) = value.parse::<i64>() {
+                        max_age = Some(match u64::try_from(seconds) {
+                            Ok(seconds) if seconds > 0 => now.saturating_add(seconds),
+                            _ => 0,
+                        });
+                    }
+                }
+                "domain" => {
+                    let value = value.trim_start_matches('.').to_ascii_lowercase();
+                    if !value.is_empty() {
+                        domain = Some(value);
+                    }
+                }
+                "path" if value.starts_with('/') => path = Some(value.to_owned()),
+                "secure" => secure = true,
+                "httponly" => http_only = true,
+                _ => {}
+            }
+        }
+
+        // Only secure origins may set secure cookies, so that a plain HTTP
+        // response cannot overwrite them.
+        if secure && !is_secure(url) {
+            return None;
+        }
+
+        // IPv6 addresses are in brackets.
+        let is_ip = host.parse::<Ipv4Addr>().is_ok() || host.starts_with('[');
+        let (domain, include_subdomains) = match domain {
+            // Without a list of public suffixes like `co.uk` there is no
+            // telling whether a parent domain is shared by unrelated sites, so
+            // only the host itself is accepted.
+            Some(domain) if domain != host => return None,
+            // Like no `Domain` at all, as there are no subdomains to share
+            // the cookie with.
+            Some(_) if is_ip || !host.contains('.') => (host, false),
+            Some(_) => (host, true),
+            None => (host, false),
+        };
+        Some(Self {
+            name: name.to_owned(),
+            value: value.trim().to_owned(),
+            domain,
+            include_subdomains,
+            path: path.unwrap_or_else(|| default_path(url)),
+            secure,
+            http_only,
+            // `Max-Age` wins over `Expires`.
+            expires: max_age.or(expires),
+        })
+    }
+
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn applies_to(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+        let domain_ok = if self.include_subdomains {
+            domain_matches(&host, &self.domain)
+        } else {
+            host == self.domain
+        };
+        domain_ok && path_matches(url.path(), &self.path) && (!self.secure || is_secure(url))
+    }
+
+    /// Whether both are the same cookie, so that one replaces the other.
+    fn is_same(&self, other: &Self) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+/// The cookies of all HTTP clients, collected from the responses to their
+/// requests and sent with later requests they apply to.
+#[derive(Default)]
+pub struct CookieJar {
+    /// in the order they were first set, which is the order they are sent in
+    /// for paths of the same length
+    cookies: Vec<StoredCookie>,
+}
+
+impl CookieJar {
+    /// The `Cookie` header to send with a request for `url`, if any cookies
+    /// apply to it.
+    pub fn header(&self, url: &Url) -> Option<HeaderValue> {
+        let now = now();
+        let mut cookies: Vec<_> = self
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now) && cookie.applies_to(url))
+            .collect();
+        if cookies.is_empty() {
+            return None;
+        }
+        // More specific paths go first.
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+
+        let mut header = String::new();
+        for cookie in cookies {
+            if !header.is_empty() {
+                header.push_str("; ");
+            }
+            let _ = write!(header, "{}={}", cookie.name, cookie.value);
+        }
+        HeaderValue::from_str(&header).ok()
+    }
+
+    /// Stores the cookies set by the response to a request for `url`.
+    pub fn store(&mut self, url: &Url, headers: &HeaderMap) {
+        let now = now();
+        for set_cookie in headers.get_all(SET_COOKIE) {
+            let cookie = set_cookie
+                .to_str()
+                .ok()
+                .and_then(|set_cookie| StoredCookie::parse(set_cookie, url, now));
+            if let Some(cookie) = cookie {
+                self.insert(cookie, now);
+            }
+        }
+    }
+
+    /// Adds the cookie, replacing the one with the same name, domain and path.
+    /// An expired cookie only removes the one it replaces.
+    fn insert(&mut self, cookie: StoredCookie, now: u64) {
+        let existing = self.cookies.iter().position(|c| c.is_same(&cookie));
+        match (existing, cookie.is_expired(now)) {
+            (Some(i), true) => {
+                self.cookies.remove(i);
+            }
+            (Some(i), false) => self.cookies[i] = cookie,
+            (None, true) => {}
+            (None, false) => self.cookies.push(cookie),
+        }
+    }
+
+    /// The cookies that have not expired.
+    pub fn to_roc(&self, ops: &RocOps) -> RocList<Cookie> {
+        let now = now();
+        let cookies: Vec<_> = self
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .map(|cookie| Cookie::new(cookie, ops))
+            .collect();
+        RocList::from_slice(&cookies, ops)
+    }
+
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+
+    /// Adds the cookies of a file in the Netscape cookie file format, as
+    /// written by [`CookieJar::save`] and by curl and wget.
+    ///
+    /// Expired cookies are skipped. A line that is not a cookie is reported
+    /// as `InvalidData`, including the line number.
+    pub fn load(&mut self, src: &str) -> io::Result<()> {
+        let now = now();
+        let mut cookies = Vec::new();
+        for (i, line) in src.lines().enumerate() {
+            let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+                Some(line) => (line, true),
+                None => (line, false),
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let cookie = parse_cookie_line(line, http_only).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: not a cookie", i + 1),
+                )
+            })?;
+            cookies.push(cookie);
+        }
+        // Only change the jar once the whole file is known to be valid.
+        for cookie in cookies {
+            self.insert(cookie, now);
+        }
+        Ok(())
+    }
+
+    /// The cookies that have not expired in the Netscape cookie file format.
+    /// Session cookies are included, with an expiry of 0.
+    pub fn save(&self) -> String {
+        let now = now();
+        let mut file = String::from("# Netscape HTTP Cookie File\n");
+        for cookie in self.cookies.iter().filter(|cookie| !cookie.is_expired(now)) {
+            let _ = writeln!(
+                file,
+                "{}{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                if cookie.http_only { "#HttpOnly_" } else { "" },
+                if cookie.include_subdomains { "." } else { "" },
+                cookie.domain,
+                if cookie.include_subdomains {
+                    "TRUE"
+                } else {
+                    "FALSE"
+                },
+                cookie.path,
+                if cookie.secure { "TRUE" } else { "FALSE" },
+                cookie.expires.unwrap_or(0),
+                cookie.name,
+                cookie.value,
+            );
+        }
+        file
+    }
+}
+
+/// Parses `domain include_subdomains path secure expires name value`,
+/// separated by tabs. The value may be missing.
+fn parse_cookie_line(line: &str, http_only: bool) -> Option<StoredCookie> {
+    let mut fields = line.split('\t');
+    let mut next = || fields.next();
+    let domain = next()?.trim_start_matches('.').to_ascii_lowercase();
+    let include_subdomains = parse_flag(next()?)?;
+    let path = next()?.to_owned();
+    let secure = parse_flag(next()?)?;
+    let expires = next()?.parse::<u64>().ok()?;
+    let name = next()?.to_owned();
+    let value = next().unwrap_or("").to_owned();
+    if domain.is_empty() || name.is_empty() || HeaderValue::from_str(&value).is_err() {
+        return None;
+    }
+    Some(StoredCookie {
+        name,
+        value,
+        domain,
+        include_subdomains,
+        path,
+        secure,
+        http_only,
+        expires: (expires > 0).then_some(expires),
+    })
+}
+
+fn parse_flag(flag: &str) -> Option<bool> {
+    match flag {
+        "TRUE" => Some(true),
+        "FALSE" => Some(false),
+        _ => None,
+    }
+}
+
+fn is_secure(url: &Url) -> bool {
+    matches!(url.scheme(), "https" | "wss")
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Whether a cookie with the path `cookie_path` is sent with a request for
+/// `path`: `/docs` applies to `/docs` and `/docs/web`, but not to `/docsets`.
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    path.strip_prefix(cookie_path)
+        .is_some_and(|rest| rest.is_empty() || cookie_path.ends_with('/') || rest.starts_with('/'))
+}
+
+/// The path of a cookie without a `Path` attribute: the directory of the
+/// requested path.
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_owned(),
+        Some(i) => url.path()[..i].to_owned(),
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Parses the date of an `Expires` attribute into seconds since the Unix
+/// epoch, using the lenient algorithm of RFC 6265, so that the common
+/// `Sun, 06 Nov 1994 08:49:37 GMT` as well as older formats are understood.
+/// Dates before the epoch are 0.
+fn parse_date(date: &str) -> Option<u64> {
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+    let tokens = date
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == ':'))
+        .filter(|token| !token.is_empty());
+    for token in tokens {
+        let digits = token.bytes().all(|b| b.is_ascii_digit());
+        if time.is_none()
+            && let Some(t) = parse_time(token)
+        {
+            time = Some(t);
+        } else if day.is_none() && digits && token.len() <= 2 {
+            day = token.parse::<i64>().ok();
+        } else if month.is_none()
+            && let Some(m) = MONTHS.iter().position(|m| {
+                token
+                    .get(..3)
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(m))
+            })
+        {
+            month = Some(m as i64 + 1);
+        } else if year.is_none() && digits && (2..=4).contains(&token.len()) {
+            year = token.parse::<i64>().ok();
+        }
+    }
+
+    let (hours, minutes, seconds) = time?;
+    let day = day.filter(|day| (1..=31).contains(day))?;
+    let year = match year? {
+        year @ 70..=99 => year + 1900,
+        year @ 0..=69 => year + 2000,
+        year => year,
+    };
+    if year < 1601 || hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    let secs = days_from_civil(year, month?, day) * 86400 + hours * 3600 + minutes * 60 + seconds;
+    Some(secs.max(0) as u64)
+}
+
+/// Parses `hh:mm:ss`, with one or two digits each.
+fn parse_time(token: &str) -> Option<(i64, i64, i64)> {
+    let mut parts = token.split(':');
+    let mut next = || {
+        parts
+            .next()
+            .filter(|part| (1..=2).contains(&part.len()))
+            .and_then(|part| part.parse::<i64>().ok())
+    };
+    let time = (next()?, next()?, next()?);
+    parts.next().is_none().then_some(time)
+}
+
+/// The number of days between the Unix epoch and the given date of the
+/// proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    fn parse(set_cookie: &str, url_str: &str) -> Option<StoredCookie> {
+        StoredCookie::parse(set_cookie, &url(url_str), NOW)
+    }
+
+    #[test]
+    fn days() {
+        for ((year, month, day), expected) in [
+            ((1970, 1, 1), 0),
+            ((1969, 12, 31), -1),
+            ((2000, 3, 1), 951_868_800 / 86400),
+            ((2024, 2, 29), 1_709_164_800 / 86400),
+            ((2100, 1, 1), 4_102_444_800 / 86400),
+            ((1601, 1, 1), -11_644_473_600 / 86400),
+        ] {
+            assert_eq!(
+                days_from_civil(year, month, day),
+                expected,
+                "{year}-{month}-{day}"
+            );
+        }
+    }
+
+    #[test]
+    fn dates() {
+        for (date, expected) in [
+            ("Sun, 06 Nov 1994 08:49:37 GMT", Some(784_111_777)),
+            ("Sunday, 06-Nov-94 08:49:37 GMT", Some(784_111_777)),
+            ("Sun Nov  6 08:49:37 1994", Some(784_111_777)),
+            ("sun, 6 NOVEMBER 1994 8:49:37 gmt", Some(784_111_777)),
+            ("Tue, 19 Jan 2038 03:14:08 GMT", Some(2_147_483_648)),
+            ("Thu, 01 Jan 1970 00:00:00 GMT", Some(0)),
+            ("Wed, 31 Dec 1969 23:59:59 GMT", Some(0)),
+            ("Thu, 01 Jan 70 00:00:00 GMT", Some(0)),
+            ("Sat, 01 Jan 00 00:00:00 GMT", Some(946_684_800)),
+            ("Sun, 06 Nov 1994", None),
+            ("Sun, 32 Nov 1994 08:49:37 GMT", None),
+            ("Sun, 06 Nov 1994 24:00:00 GMT", None),
+            ("Sun, 06 Nov 1994 08:60:00 GMT", None),
+            ("Sun, 06 Foo 1994 08:49:37 GMT", None),
+            ("Mon, 01 Jan 1600 00:00:00 GMT", None),
+            ("not a date", None),
+            ("", None),
+        ] {
+            assert_eq!(parse_date(date), expected, "{date:?}");
+        }
+    }
+
+    #[test]
+    fn domains() {
+        for (host, domain, expected) in [
+            ("example.com", "example.com", true),
+            ("www.example.com", "example.com", true),
+            ("a.b.example.com", "example.com", true),
+            ("badexample.com", "example.com", false),
+            ("example.com", "www.example.com", false),
+            ("example.org", "example.com", false),
+        ] {
+            assert_eq!(
+                domain_matches(host, domain),
+                expected,
+                "{host:?} in {domain:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn paths() {
+        for (path, cookie_path, expected) in [
+            ("/", "/", true),
+            ("/docs", "/", true),
+            ("/docs", "/docs", true),
+            ("/docs/", "/docs", true),
+            ("/docs/web", "/docs", true),
+            ("/docs/web", "/docs/", true),
+            ("/docsets", "/docs", false),
+            ("/doc", "/docs", false),
+            ("/", "/docs", false),
+        ] {
+            assert_eq!(
+                path_matches(path, cookie_path),
+                expected,
+                "{path:?} with {cookie_path:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn default_paths() {
+        for (url_str, expected) in [
+            ("http://example.com", "/"),
+            ("http://example.com/", "/"),
+            ("http://example.com/docs", "/"),
+            ("http://example.com/docs/", "/docs"),
+            ("http://example.com/docs/web", "/docs"),
+            ("http://example.com/docs/web?q=/a/b", "/docs"),
+        ] {
+            assert_eq!(default_path(&url(url_str)), expected, "{url_str:?}");
+        }
+    }
+
+    #[test]
+    fn expiry() {
+        for (set_cookie, expected) in [
+            ("a=b", None),
+            ("a=b; Max-Age=60", Some(NOW + 60)),
+            (
+                "a=b; Expires=Sun, 06 Nov 1994 08:49:37 GMT",
+                Some(784_111_777),
+            ),
+            // `Max-Age` wins, no matter the order.
+            (
+                "a=b; Max-Age=60; Expires=Sun, 06 Nov 1994 08:49:37 GMT",
+                Some(NOW + 60),
+            ),
+            (
+                "a=b; Expires=Sun, 06 Nov 1994 08:49:37 GMT; Max-Age=60",
+                Some(NOW + 60),
+            ),
+            ("a=b; Max-Age=0", Some(0)),
+            ("a=b; Max-Age=-1", Some(0)),
+            // Invalid values are ignored.
+            (
+                "a=b; Max-Age=soon; Expires=Sun, 06 Nov 1994 08:49:37 GMT",
+                Some(784_111_777),
+            ),
+            ("a=b; Expires=never", None),
+        ] {
+            let cookie = parse(set_cookie, "http://example.com/").unwrap();
+            assert_eq!(cookie.expires, expected, "{set_cookie:?}");
+        }
+    }
+
+    #[test]
+    fn domain_attribute() {
+        for (url_str, set_cookie, expected) in [
+            (
+                "http://www.example.com/",
+                "a=b",
+                Some(("www.example.com", false)),
+            ),
+            (
+                "http://www.example.com/",
+                "a=b; Domain=www.example.com",
+                Some(("www.example.com", true)),
+            ),
+            (
+                "http://example.com/",
+                "a=b; Domain=.Example.COM",
+                Some(("example.com", true)),
+            ),
+            // Parent domains could be public suffixes.
+            ("http://www.example.com/", "a=b; Domain=example.com", None),
+            ("http://www.example.co.uk/", "a=b; Domain=co.uk", None),
+            ("http://www.example.com/", "a=b; Domain=com", None),
+            ("http://www.example.com/", "a=b; Domain=other.com", None),
+            ("http://example.com/", "a=b; Domain=www.example.com", None),
+            (
+                "http://localhost/",
+                "a=b; Domain=localhost",
+                Some(("localhost", false)),
+            ),
+            (
+                "http://127.0.0.1/",
+                "a=b; Domain=127.0.0.1",
+                Some(("127.0.0.1", false)),
+            ),
+            ("http://127.0.0.1/", "a=b; Domain=0.0.1", None),
+            ("http://[::1]/", "a=b", Some(("[::1]", false))),
+            ("http://[::1]/", "a=b; Domain=1]", None),
+        ] {
+            let cookie = parse(set_cookie, url_str);
+            let actual = cookie
+                .as_ref()
+                .map(|cookie| (cookie.domain.as_str(), cookie.include_subdomains));
+            assert_eq!(actual, expected, "{set_cookie:?} from {url_str:?}");
+        }
+    }
+
+    #[test]
+    fn secure_attribute() {
+        for (url_str, stored) in [
+            ("https://www.example.com/", true),
+            ("wss://www.example.com/", true),
+            ("http://www.example.com/", false),
+            ("ws://www.example.com/", false),
+        ] {
+            let cookie = parse("a=b; Secure", url_str);
+            assert_eq!(cookie.is_some(), stored, "{url_str:?}");
+        }
+    }
+
+    #[test]
+    fn header() {
+        let mut jar = CookieJar::default();
+        for (set_cookie, url_str) in [
+            ("root=1; Path=/", "https://www.example.com/"),
+            ("docs=2; Path=/docs", "https://www.example.com/"),
+            (
+                "shared=3; Domain=example.com; Path=/",
+                "https://example.com/",
+            ),
+            ("secure=4; Secure; Path=/", "https://www.example.com/"),
+            ("gone=5; Max-Age=0", "https://www.example.com/"),
+        ] {
+            let cookie = parse(set_cookie, url_str).unwrap();
+            jar.insert(cookie, now());
+        }
+
+        for (url_str, expected) in [
+            (
+                "https://www.example.com/",
+                Some("root=1; shared=3; secure=4"),
+            ),
+            (
+                "https://www.example.com/docs/web",
+                Some("docs=2; root=1; shared=3; secure=4"),
+            ),
+            ("http://www.example.com/", Some("root=1; shared=3")),
+            ("http://api.example.com/", Some("shared=3")),
+            ("http://example.org/", None),
+        ] {
+            let header = jar.header(&url(url_str));
+            assert_eq!(
+                header.as_ref().map(|h| h.to_str().unwrap()),
+                expected,
+                "{url_str:?}"
+            );
+        }
+    }
+
+    const FILE: &str = "# Netscape HTTP Cookie File
+.example.com\tTRUE\t/\tFALSE\t4102444800\tshared\t1
+#HttpOnly_www.example.com\tFALSE\t/docs\tTRUE\t0\tsession\t2
+
+# expired
+www.example.com\tFALSE\t/\tFALSE\t1\told\t3
+www.example.com\tFALSE\t/\tFALSE\t4102444800\tempty
+";
+
+    #[test]
+    fn load() {
+        let mut jar = CookieJar::default();
+        jar.load(FILE).unwrap();
+
+        let [shared, session, empty] = jar.cookies.as_slice() else {
+            panic!("loaded {:?}", jar.cookies);
+        };
+        assert_eq!(
+            (
+                shared.name.as_str(),
+                shared.domain.as_str(),
+                shared.include_subdomains,
+                shared.http_only,
+                shared.expires
+            ),
+            ("shared", "example.com", true, false, Some(4_102_444_800))
+        );
+        assert_eq!(
+            (
+                session.name.as_str(),
+                session.domain.as_str(),
+                session.path.as_str(),
+                session.secure,
+                session.http_only,
+                session.expires
+            ),
+            ("session", "www.example.com", "/docs", true, true, None)
+        );
+        assert_eq!((empty.name.as_str(), empty.value.as_str()), ("empty", ""));
+    }
+
+    #[test]
+    fn load_invalid() {
+        for (src, line) in [
+            ("example.com\tTRUE\t/\tFALSE\t0\n", 1),
+            ("# ok\nexample.com\tYES\t/\tFALSE\t0\ta\tb\n", 2),
+            ("example.com\tTRUE\t/\tFALSE\tnever\ta\tb\n", 1),
+            ("\tTRUE\t/\tFALSE\t0\ta\tb\n", 1),
+            ("example.com\tTRUE\t/\tFALSE\t0\t\tb\n", 1),
+        ] {
+            // The valid cookies before the broken line are not loaded either.
+            let mut jar = CookieJar::default();
+            let e = jar.load(&format!("{FILE}{src}")).unwrap_err();
+
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData, "{src:?}");
+            assert_eq!(
+                e.to_string(),
+                format!("line {}: not a cookie", FILE.lines().count() + line),
+                "{src:?}"
+            );
+            assert!(jar.cookies.is_empty(), "{src:?} changed the jar");
+        }
+    }
+
+    #[test]
+    fn save() {
+        let mut jar = CookieJar::default();
+        jar.load(FILE).unwrap();
+
+        assert_eq!(
+            jar.save(),
+            "# Netscape HTTP Cookie File
+.example.com\tTRUE\t/\tFALSE\t4102444800\tshared\t1
+#HttpOnly_www.example.com\tFALSE\t/docs\tTRUE\t0\tsession\t2
+www.example.com\tFALSE\t/\tFALSE\t4102444800\tempty\t
+"
+        );
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut jar = CookieJar::default();
+        for set_cookie in [
+            "plain=1",
+            "shared=2; Domain=www.example.com; Path=/docs; Max-Age=3600",
+            "flags=3; Secure; HttpOnly; Expires=Fri, 01 Jan 2100 00:00:00 GMT",
+        ] {
+            let cookie =
+                StoredCookie::parse(set_cookie, &url("https://www.example.com/a/b"), now())
+                    .unwrap();
+            jar.insert(cookie, now());
+        }
+
+        assert_eq!(jar.cookies.len(), 3);
+
+        let mut loaded = CookieJar::default();
+        loaded.load(&jar.save()).unwrap();
+
+        assert_eq!(loaded.save(), jar.save());
+        let fields = |jar: &CookieJar| -> Vec<String> {
+            jar.cookies
+                .iter()
+                .map(|cookie| format!("{cookie:?}"))
+                .collect()
+        };
+        assert_eq!(fields(&loaded), fields(&jar));
+    }
+}
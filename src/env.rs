@@ -0,0 +1,488 @@
+use std::{os::unix::ffi::OsStrExt, path::PathBuf};
+
+use roc_platform_builder::roc_std_new::{RocOps, RocRefcounted, RocStr};
+
+/// A `(Str, Str)` tuple holding an environment variable's name and value.
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct EnvPair {
+    key: RocStr,
+    value: RocStr,
+}
+
+impl EnvPair {
+    pub fn new(key: &str, value: &str, ops: &RocOps) -> Self {
+        Self {
+            key: RocStr::from_str(key, ops),
+            value: RocStr::from_str(value, ops),
+        }
+    }
+}
+
+impl RocRefcounted for EnvPair {
+    fn inc(&mut self) {
+        self.key.inc();
+        self.value.inc();
+    }
+    fn dec(&mut self) {
+        self.key.dec();
+        self.value.dec();
+    }
+    fn is_refcounted() -> bool {
+        true
+    }
+}
+
+/// Sets the variables in the environment of the current process, except
+/// those that are already set.
+///
+/// Changing the environment is only sound while no other thread may read it,
+/// so this fails unless the current thread is the only one. Threads are
+/// started by HTTP clients and to feed or read spawned processes.
+pub fn load(pairs: Vec<(String, String)>) -> std::io::Result<()> {
+    if !is_single_threaded()? {
+        return Err(std::io::Error::other(
+            "variables can only be loaded before any HTTP request is sent or process is spawned",
+        ));
+    }
+    for (key, value) in pairs {
+        if std::env::var_os(&key).is_none() {
+            // SAFETY: there is no other thread that could read the
+            // environment concurrently, and only this one could start one.
+            unsafe { std::env::set_var(key, value) };
+        }
+    }
+    Ok(())
+}
+
+/// Whether the current thread is the only one of the process.
+fn is_single_threaded() -> std::io::Result<bool> {
+    let mut threads = std::fs::read_dir("/proc/self/task")?;
+    Ok(threads.next().is_some() && threads.next().is_none())
+}
+
+/// Returns the user's home directory, read from `HOME` or, if it is unset,
+/// empty or a relative path, from the passwd database.
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| {
+            let uid = unsafe { libc::getuid() };
+            with_passwd(uid, |passwd| {
+                let dir = unsafe { std::ffi::CStr::from_ptr(passwd.pw_dir) };
+                PathBuf::from(std::ffi::OsStr::from_bytes(dir.to_bytes()))
+            })
+            .filter(|p| p.is_absolute())
+        })
+}
+
+/// Resolves an XDG base directory.
+///
+/// As required by the [XDG Base Directory Specification](https://specifications.freedesktop.org/basedir-spec/latest/),
+/// the variable is ignored if it is empty or holds a relative path, in which
+/// case `fallback` (relative to the home directory) is used instead.
+pub fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| home_dir().map(|home| home.join(fallback)))
+}
+
+/// Returns `$XDG_RUNTIME_DIR`. The specification defines no fallback for it.
+pub fn runtime_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+}
+
+/// Returns `TMPDIR` if it is set and not empty, otherwise `/tmp`.
+pub fn temp_dir() -> PathBuf {
+    std::env::var_os("TMPDIR")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| PathBuf::from("/tmp"), PathBuf::from)
+}
+
+/// Looks up the name of the user with the given uid in the passwd database.
+pub fn username(uid: libc::uid_t) -> Option<String> {
+    with_passwd(uid, |passwd| {
+        let name = unsafe { std::ffi::CStr::from_ptr(passwd.pw_name) };
+        name.to_string_lossy().into_owned()
+    })
+}
+
+/// Calls `f` with the passwd database entry of the user with the given uid.
+fn with_passwd<T>(uid: libc::uid_t, f: impl FnOnce(&libc::passwd) -> T) -> Option<T> {
+    let mut buf = vec![0 as libc::c_char; 1024];
+    loop {
+        let mut passwd = std::mem::MaybeUninit::<libc::passwd>::uninit();
+        let mut result = std::ptr::null_mut();
+        let rc = unsafe {
+            libc::getpwuid_r(
+                uid,
+                passwd.as_mut_ptr(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut result,
+            )
+        };
+        if rc == libc::ERANGE {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+        return Some(f(unsafe { &*result }));
+    }
+}
+
+/// Returns the host name of the machine.
+pub fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    let rc = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+    if rc != 0 {
+        return None;
+    }
+    let name = std::ffi::CStr::from_bytes_until_nul(&buf).ok()?;
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Parses the contents of a [dotenv](https://hexdocs.pm/dotenvy/dotenv_file_format.html) file.
+///
+/// Supports `#` comments, an optional `export` prefix, single-quoted (literal),
+/// double-quoted (escapes, may span lines) and unquoted values. `${VAR}` is
+/// interpolated in double-quoted and unquoted values, first from the pairs
+/// defined earlier in the file, then from the process environment.
+pub fn parse_dotenv(src: &str) -> std::io::Result<Vec<(String, String)>> {
+    DotenvParser {
+        chars: src.chars().peekable(),
+        line: 1,
+        pairs: Vec::new(),
+    }
+    .parse()
+}
+
+struct DotenvParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    pairs: Vec<(String, String)>,
+}
+
+impl DotenvParser<'_> {
+    fn parse(mut self) -> std::io::Result<Vec<(String, String)>> {
+        loop {
+            self.skip_while(char::is_whitespace);
+            match self.chars.peek() {
+                None => return Ok(self.pairs),
+                Some('#') => self.skip_while(|c| c != '\n'),
+                Some(_) => {
+                    let (key, value) = self.parse_pair()?;
+                    match self.pairs.iter_mut().find(|(k, _)| *k == key) {
+                        Some(pair) => pair.1 = value,
+                        None => self.pairs.push((key, value)),
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_pair(&mut self) -> std::io::Result<(String, String)> {
+        let mut key = self.parse_key()?;
+        if key == "export" && self.chars.peek().is_some_and(|c| *c == ' ' || *c == '\t') {
+            self.skip_while(is_blank);
+            key = self.parse_key()?;
+        }
+
+        self.skip_while(is_blank);
+        if self.chars.next_if_eq(&'=').is_none() {
+            return Err(self.error(format!("expected `=` after `{key}`")));
+        }
+        self.skip_while(is_blank);
+
+        let value = match self.chars.peek() {
+            Some('\'') => self.parse_single_quoted()?,
+            Some('"') => self.parse_double_quoted()?,
+            _ => return Ok((key, self.parse_unquoted()?)),
+        };
+
+        self.skip_while(is_blank);
+        match self.chars.peek() {
+            None | Some('\n') => {}
+            Some('#') => self.skip_while(|c| c != '\n'),
+            Some(&c) => return Err(self.error(format!("unexpected `{c}` after closing quote"))),
+        }
+
+        Ok((key, value))
+    }
+
+    fn parse_key(&mut self) -> std::io::Result<String> {
+        let mut key = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        {
+            key.push(c);
+        }
+        if key.is_empty() {
+            return Err(self.error("expected a variable name".into()));
+        }
+        Ok(key)
+    }
+
+    fn parse_single_quoted(&mut self) -> std::io::Result<String> {
+        self.chars.next();
+        let mut value = String::new();
+        loop {
+            match self.next_char() {
+                Some('\'') => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated single-quoted value".into())),
+            }
+        }
+    }
+
+    fn parse_double_quoted(&mut self) -> std::io::Result<String> {
+        self.chars.next();
+        let mut value = String::new();
+        loop {
+            match self.next_char() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.next_char() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('"' | '\\' | '$')) => value.push(c),
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    None => return Err(self.error("unterminated double-quoted value".into())),
+                },
+                Some('$') if self.chars.peek() == Some(&'{') => {
+                    let interpolated = self.parse_interpolation()?;
+                    value.push_str(&interpolated);
+                }
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated double-quoted value".into())),
+            }
+        }
+    }
+
+    fn parse_unquoted(&mut self) -> std::io::Result<String> {
+        let mut value = String::new();
+        while let Some(c) = self.chars.next_if(|c| *c != '\n') {
+            match c {
+                '#' if value.ends_with(is_blank) || value.is_empty() => {
+                    self.skip_while(|c| c != '\n');
+                    break;
+                }
+                '$' if self.chars.peek() == Some(&'{') => {
+                    let interpolated = self.parse_interpolation()?;
+                    value.push_str(&interpolated);
+                }
+                c => value.push(c),
+            }
+        }
+        Ok(value.trim_end_matches(is_blank).to_owned())
+    }
+
+    /// Parses `{NAME}` (the `$` has already been consumed) and returns its value.
+    fn parse_interpolation(&mut self) -> std::io::Result<String> {
+        self.chars.next();
+        let mut name = String::new();
+        loop {
+            match self.chars.next_if(|c| *c != '\n') {
+                Some('}') => break,
+                Some(c) => name.push(c),
+                None => return Err(self.error("unterminated `${`".into())),
+            }
+        }
+        let value = self
+            .pairs
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.clone())
+            .or_else(|| std::env::var(&name).ok())
+            .unwrap_or_default();
+        Ok(value)
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn skip_while(&mut self, mut predicate: impl FnMut(char) -> bool) {
+        while self.chars.peek().is_some_and(|c| predicate(*c)) {
+            self.next_char();
+        }
+    }
+
+    fn error(&self, message: String) -> std::io::Error {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("line {}: {message}", self.line),
+        )
+    }
+}
+
+fn is_blank(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+/// Returns the raw bytes of the command-line arguments, including the program name.
+///
+/// `std::env::args_os` is only populated when the C runtime passes `argv` to
+/// Rust's startup code, which musl does not do, so `/proc/self/cmdline` is
+/// read as a fallback.
+pub fn args_bytes() -> Vec<Vec<u8>> {
+    use std::os::unix::ffi::OsStringExt;
+
+    let args: Vec<_> = std::env::args_os().map(OsStringExt::into_vec).collect();
+    if !args.is_empty() {
+        return args;
+    }
+
+    std::fs::read("/proc/self/cmdline")
+        .map(|cmdline| {
+            let cmdline = cmdline.strip_suffix(&[0]).unwrap_or(&cmdline);
+            cmdline.split(|b| *b == 0).map(<[u8]>::to_vec).collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(src: &str) -> Vec<(String, String)> {
+        parse_dotenv(src).unwrap_or_else(|e| panic!("{src:?} failed to parse: {e}"))
+    }
+
+    fn value(src: &str) -> String {
+        match pairs(src).as_slice() {
+            [(_, value)] => value.clone(),
+            pairs => panic!("{src:?} has {} pairs", pairs.len()),
+        }
+    }
+
+    fn error(src: &str) -> String {
+        match parse_dotenv(src) {
+            Ok(pairs) => panic!("{src:?} parsed to {pairs:?}"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn quotes() {
+        for (src, expected) in [
+            ("A=plain", "plain"),
+            ("A=  padded value  ", "padded value"),
+            ("A='single ${B} \\n'", "single ${B} \\n"),
+            ("A=\"double\"", "double"),
+            ("A=\"it's\"", "it's"),
+            ("A='say \"hi\"'", "say \"hi\""),
+            ("A=", ""),
+            ("A=''", ""),
+        ] {
+            assert_eq!(value(src), expected, "{src:?}");
+        }
+    }
+
+    #[test]
+    fn escapes() {
+        for (src, expected) in [
+            (r#"A="a\nb""#, "a\nb"),
+            (r#"A="a\rb\tc""#, "a\rb\tc"),
+            (r#"A="\"quoted\"""#, "\"quoted\""),
+            (r#"A="back\\slash""#, "back\\slash"),
+            (r#"A="\${NOT}""#, "${NOT}"),
+            (r#"A="\q""#, "\\q"),
+            (r"A=a\nb", "a\\nb"),
+        ] {
+            assert_eq!(value(src), expected, "{src:?}");
+        }
+    }
+
+    #[test]
+    fn export_prefix() {
+        for (src, key) in [
+            ("export A=1", "A"),
+            ("export\tA=1", "A"),
+            ("export  A = 1", "A"),
+            ("export=1", "export"),
+            ("exported=1", "exported"),
+        ] {
+            assert_eq!(pairs(src), [(key.to_owned(), "1".to_owned())], "{src:?}");
+        }
+    }
+
+    #[test]
+    fn comments() {
+        for (src, expected) in [
+            ("# comment\nA=1", "1"),
+            ("  # indented comment\nA=1\n# trailing", "1"),
+            ("A=1 # inline", "1"),
+            ("A=1#not a comment", "1#not a comment"),
+            ("A=# only a comment", ""),
+            ("A='1' # after quotes", "1"),
+            ("A=\"1\"# after quotes", "1"),
+            ("A='# kept'", "# kept"),
+        ] {
+            assert_eq!(value(src), expected, "{src:?}");
+        }
+    }
+
+    #[test]
+    fn interpolation() {
+        for (src, expected) in [
+            ("B=b\nA=${B}", "b"),
+            ("B=b\nA=\"x${B}y\"", "xby"),
+            ("B=b\nA='${B}'", "${B}"),
+            ("A=${BASIC_CLI_TEST_UNDEFINED}", ""),
+            ("A=\"<${BASIC_CLI_TEST_UNDEFINED}>\"", "<>"),
+            ("B=first\nB=second\nA=${B}", "second"),
+            ("A=$B", "$B"),
+        ] {
+            let pairs = pairs(src);
+            let (_, value) = pairs.iter().find(|(key, _)| key == "A").unwrap();
+            assert_eq!(value, expected, "{src:?}");
+        }
+    }
+
+    #[test]
+    fn multiline() {
+        assert_eq!(value("A=\"line 1\nline 2\""), "line 1\nline 2");
+        assert_eq!(value("A='line 1\nline 2'"), "line 1\nline 2");
+        assert_eq!(
+            pairs("A=\"1\n2\"\nB=3"),
+            [("A".into(), "1\n2".into()), ("B".into(), "3".into())]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        for (src, expected) in [
+            ("A", "line 1: expected `=` after `A`"),
+            ("A=1\n=2", "line 2: expected a variable name"),
+            ("A=1\nB='open", "line 2: unterminated single-quoted value"),
+            ("A=\"1\n2\nC", "line 3: unterminated double-quoted value"),
+            ("A='1' x", "line 1: unexpected `x` after closing quote"),
+            ("\n\nA=${B", "line 3: unterminated `${`"),
+        ] {
+            assert_eq!(error(src), expected, "{src:?}");
+        }
+    }
+
+    #[test]
+    fn load_needs_a_single_thread() {
+        // The test harness runs the tests on threads of their own.
+        let e = load(vec![("BASIC_CLI_TEST_LOADED".into(), "value".into())]).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::Other);
+        assert_eq!(std::env::var_os("BASIC_CLI_TEST_LOADED"), None);
+    }
+}
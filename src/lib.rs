@@ -1,10 +1,9 @@
 use std::{
     io::{Read, stdin},
-    mem::ManuallyDrop,
+    mem::{ManuallyDrop, MaybeUninit},
     process::ExitCode,
 };
 
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use roc_command::CommandOutputSuccess;
 use roc_io_error::IOErr;
 use roc_platform_builder::{RocHost, RocSingleTagWrapper, host, platform_init};
@@ -15,27 +14,59 @@ use roc_platform_builder::roc_std_new::{self as roc, RocList};
 
 use roc::RocStr;
 
-use crate::http::Response;
-
+mod cmd;
+mod cookie;
+mod env;
 mod http;
+mod process;
+mod pty;
+
+#[repr(C)]
+pub struct CmdExitCodeErr {
+    cmd_err: MaybeUninit<roc_io_error::IOErr>,
+    discriminant: u8, // CmdErr=0, TimedOut=1
+}
+
+impl CmdExitCodeErr {
+    pub fn cmd_err(io_err: roc_io_error::IOErr) -> Self {
+        Self {
+            cmd_err: MaybeUninit::new(io_err),
+            discriminant: 0,
+        }
+    }
+
+    pub fn timed_out() -> Self {
+        Self {
+            cmd_err: MaybeUninit::zeroed(),
+            discriminant: 1,
+        }
+    }
+}
 
 #[repr(C)]
 pub struct NonZeroExitPayload {
+    pub stderr_utf8_lossy: RocStr,   // offset 0 (24 bytes)
+    pub stdout_utf8_lossy: RocStr,   // offset 24 (24 bytes)
+    pub status: process::ExitStatus, // offset 48 (12 bytes + padding)
+}
+
+#[repr(C)]
+pub struct TimedOutPayload {
     pub stderr_utf8_lossy: RocStr, // offset 0 (24 bytes)
     pub stdout_utf8_lossy: RocStr, // offset 24 (24 bytes)
-    pub exit_code: i32,            // offset 48 (4 bytes + padding)
 }
 
 #[repr(C)]
 pub union CmdOutputErrPayload {
     cmd_err: ManuallyDrop<roc_io_error::IOErr>,
     non_zero_exit: ManuallyDrop<NonZeroExitPayload>,
+    timed_out: ManuallyDrop<TimedOutPayload>,
 }
 
 #[repr(C)]
 pub struct CmdOutputErr {
     payload: CmdOutputErrPayload,
-    discriminant: u8, // CmdErr=0, NonZeroExit=1
+    discriminant: u8, // CmdErr=0, NonZeroExit=1, TimedOut=2
 }
 
 impl CmdOutputErr {
@@ -51,14 +82,187 @@ impl CmdOutputErr {
     pub fn non_zero_exit(
         stderr_utf8_lossy: RocStr,
         stdout_utf8_lossy: RocStr,
-        exit_code: i32,
+        status: process::ExitStatus,
     ) -> Self {
         Self {
             payload: CmdOutputErrPayload {
                 non_zero_exit: core::mem::ManuallyDrop::new(NonZeroExitPayload {
                     stderr_utf8_lossy,
                     stdout_utf8_lossy,
-                    exit_code,
+                    status,
+                }),
+            },
+            discriminant: 1,
+        }
+    }
+
+    pub fn timed_out(stderr_utf8_lossy: RocStr, stdout_utf8_lossy: RocStr) -> Self {
+        Self {
+            payload: CmdOutputErrPayload {
+                timed_out: core::mem::ManuallyDrop::new(TimedOutPayload {
+                    stderr_utf8_lossy,
+                    stdout_utf8_lossy,
+                }),
+            },
+            discriminant: 2,
+        }
+    }
+}
+
+#[repr(C)]
+pub struct PtyOutputSuccess {
+    pub output_utf8_lossy: RocStr,   // offset 0 (24 bytes)
+    pub status: process::ExitStatus, // offset 24 (12 bytes + padding)
+}
+
+#[repr(C)]
+pub union PtyOutputErrPayload {
+    cmd_err: ManuallyDrop<roc_io_error::IOErr>,
+    timed_out: ManuallyDrop<RocStr>,
+}
+
+#[repr(C)]
+pub struct PtyOutputErr {
+    payload: PtyOutputErrPayload,
+    discriminant: u8, // CmdErr=0, TimedOut=1
+}
+
+impl PtyOutputErr {
+    pub fn cmd_err(io_err: roc_io_error::IOErr) -> Self {
+        Self {
+            payload: PtyOutputErrPayload {
+                cmd_err: core::mem::ManuallyDrop::new(io_err),
+            },
+            discriminant: 0,
+        }
+    }
+
+    pub fn timed_out(output_utf8_lossy: RocStr) -> Self {
+        Self {
+            payload: PtyOutputErrPayload {
+                timed_out: core::mem::ManuallyDrop::new(output_utf8_lossy),
+            },
+            discriminant: 1,
+        }
+    }
+}
+
+#[repr(C)]
+pub struct CommandOutputBytesSuccess {
+    pub stderr: RocList<u8>, // offset 0 (24 bytes)
+    pub stdout: RocList<u8>, // offset 24 (24 bytes)
+}
+
+#[repr(C)]
+pub struct NonZeroExitBytesPayload {
+    pub stderr: RocList<u8>,         // offset 0 (24 bytes)
+    pub stdout: RocList<u8>,         // offset 24 (24 bytes)
+    pub status: process::ExitStatus, // offset 48 (12 bytes + padding)
+}
+
+#[repr(C)]
+pub struct TimedOutBytesPayload {
+    pub stderr: RocList<u8>, // offset 0 (24 bytes)
+    pub stdout: RocList<u8>, // offset 24 (24 bytes)
+}
+
+#[repr(C)]
+pub union CmdOutputBytesErrPayload {
+    cmd_err: ManuallyDrop<roc_io_error::IOErr>,
+    non_zero_exit: ManuallyDrop<NonZeroExitBytesPayload>,
+    timed_out: ManuallyDrop<TimedOutBytesPayload>,
+}
+
+#[repr(C)]
+pub struct CmdOutputBytesErr {
+    payload: CmdOutputBytesErrPayload,
+    discriminant: u8, // CmdErr=0, NonZeroExit=1, TimedOut=2
+}
+
+impl CmdOutputBytesErr {
+    pub fn cmd_err(io_err: roc_io_error::IOErr) -> Self {
+        Self {
+            payload: CmdOutputBytesErrPayload {
+                cmd_err: core::mem::ManuallyDrop::new(io_err),
+            },
+            discriminant: 0,
+        }
+    }
+
+    pub fn non_zero_exit(
+        stderr: RocList<u8>,
+        stdout: RocList<u8>,
+        status: process::ExitStatus,
+    ) -> Self {
+        Self {
+            payload: CmdOutputBytesErrPayload {
+                non_zero_exit: core::mem::ManuallyDrop::new(NonZeroExitBytesPayload {
+                    stderr,
+                    stdout,
+                    status,
+                }),
+            },
+            discriminant: 1,
+        }
+    }
+
+    pub fn timed_out(stderr: RocList<u8>, stdout: RocList<u8>) -> Self {
+        Self {
+            payload: CmdOutputBytesErrPayload {
+                timed_out: core::mem::ManuallyDrop::new(TimedOutBytesPayload { stderr, stdout }),
+            },
+            discriminant: 2,
+        }
+    }
+}
+
+#[repr(C)]
+pub struct PipelineOutputSuccess {
+    pub statuses: RocList<process::ExitStatus>, // offset 0 (24 bytes)
+    pub stderr_utf8_lossy: RocStr,              // offset 24 (24 bytes)
+    pub stdout_utf8: RocStr,                    // offset 48 (24 bytes)
+}
+
+#[repr(C)]
+pub struct PipelineNonZeroExitPayload {
+    pub statuses: RocList<process::ExitStatus>, // offset 0 (24 bytes)
+    pub stderr_utf8_lossy: RocStr,              // offset 24 (24 bytes)
+    pub stdout_utf8_lossy: RocStr,              // offset 48 (24 bytes)
+}
+
+#[repr(C)]
+pub union PipelineErrPayload {
+    cmd_err: ManuallyDrop<roc_io_error::IOErr>,
+    non_zero_exit: ManuallyDrop<PipelineNonZeroExitPayload>,
+}
+
+#[repr(C)]
+pub struct PipelineErr {
+    payload: PipelineErrPayload,
+    discriminant: u8, // CmdErr=0, NonZeroExit=1
+}
+
+impl PipelineErr {
+    pub fn cmd_err(io_err: roc_io_error::IOErr) -> Self {
+        Self {
+            payload: PipelineErrPayload {
+                cmd_err: core::mem::ManuallyDrop::new(io_err),
+            },
+            discriminant: 0,
+        }
+    }
+
+    pub fn non_zero_exit(
+        statuses: RocList<process::ExitStatus>,
+        stderr_utf8_lossy: RocStr,
+        stdout_utf8_lossy: RocStr,
+    ) -> Self {
+        Self {
+            payload: PipelineErrPayload {
+                non_zero_exit: core::mem::ManuallyDrop::new(PipelineNonZeroExitPayload {
+                    statuses,
+                    stderr_utf8_lossy,
+                    stdout_utf8_lossy,
                 }),
             },
             discriminant: 1,
@@ -66,7 +270,12 @@ impl CmdOutputErr {
     }
 }
 
-struct Host;
+#[derive(Default)]
+struct Host {
+    downloads: http::Downloads,
+    http: http::Clients,
+    processes: process::ProcessTable,
+}
 
 #[host]
 impl Host {
@@ -74,33 +283,161 @@ impl Host {
     fn cmd_exec_exit_code(
         &mut self,
         ops: &roc::RocOps,
-        cmd: &roc_command::Command,
-    ) -> Result<i32, RocSingleTagWrapper<IOErr>> {
-        roc_command::command_exec_exit_code(cmd, ops).map_err(|e| e.into())
+        cmd: &cmd::Command,
+    ) -> Result<process::ExitStatus, CmdExitCodeErr> {
+        let output = cmd
+            .status()
+            .map_err(|e| CmdExitCodeErr::cmd_err(IOErr::from_io_error(&e, ops)))?;
+        if output.timed_out {
+            return Err(CmdExitCodeErr::timed_out());
+        }
+        Ok(output.status.into())
     }
 
     #[fallible]
     fn cmd_exec_output(
         &mut self,
         ops: &roc::RocOps,
-        cmd: &roc_command::Command,
+        cmd: &cmd::Command,
     ) -> Result<CommandOutputSuccess, CmdOutputErr> {
-        match roc_command::command_exec_output(cmd, ops) {
-            roc_command::CommandOutputResult::Success(output) => Ok(CommandOutputSuccess {
-                stderr_utf8_lossy: output.stderr_utf8_lossy,
-                stdout_utf8: output.stdout_utf8,
-            }),
-            roc_command::CommandOutputResult::NonZeroExit(failure) => {
-                Err(CmdOutputErr::non_zero_exit(
-                    failure.stderr_utf8_lossy,
-                    failure.stdout_utf8_lossy,
-                    failure.exit_code,
-                ))
-            }
-            roc_command::CommandOutputResult::Error(io_err) => Err(CmdOutputErr::cmd_err(io_err)),
+        let output = cmd
+            .output()
+            .map_err(|e| CmdOutputErr::cmd_err(IOErr::from_io_error(&e, ops)))?;
+
+        let stdout = RocStr::from_str(&String::from_utf8_lossy(&output.stdout), ops);
+        let stderr = RocStr::from_str(&String::from_utf8_lossy(&output.stderr), ops);
+        if output.timed_out {
+            Err(CmdOutputErr::timed_out(stderr, stdout))
+        } else if output.status.success() {
+            Ok(CommandOutputSuccess {
+                stderr_utf8_lossy: stderr,
+                stdout_utf8: stdout,
+            })
+        } else {
+            Err(CmdOutputErr::non_zero_exit(
+                stderr,
+                stdout,
+                output.status.into(),
+            ))
+        }
+    }
+
+    #[fallible]
+    fn cmd_exec_output_bytes(
+        &mut self,
+        ops: &roc::RocOps,
+        cmd: &cmd::Command,
+    ) -> Result<CommandOutputBytesSuccess, CmdOutputBytesErr> {
+        let output = cmd
+            .output()
+            .map_err(|e| CmdOutputBytesErr::cmd_err(IOErr::from_io_error(&e, ops)))?;
+
+        let stdout = RocList::from_slice(&output.stdout, ops);
+        let stderr = RocList::from_slice(&output.stderr, ops);
+        if output.timed_out {
+            Err(CmdOutputBytesErr::timed_out(stderr, stdout))
+        } else if output.status.success() {
+            Ok(CommandOutputBytesSuccess { stderr, stdout })
+        } else {
+            Err(CmdOutputBytesErr::non_zero_exit(
+                stderr,
+                stdout,
+                output.status.into(),
+            ))
+        }
+    }
+
+    #[fallible]
+    fn cmd_exec_pty(
+        &mut self,
+        ops: &roc::RocOps,
+        cmd: &cmd::Command,
+        size: &pty::WindowSize,
+    ) -> Result<PtyOutputSuccess, PtyOutputErr> {
+        let output = pty::output(cmd, *size)
+            .map_err(|e| PtyOutputErr::cmd_err(IOErr::from_io_error(&e, ops)))?;
+
+        let output_utf8_lossy = RocStr::from_str(&String::from_utf8_lossy(&output.stdout), ops);
+        if output.timed_out {
+            return Err(PtyOutputErr::timed_out(output_utf8_lossy));
+        }
+        Ok(PtyOutputSuccess {
+            output_utf8_lossy,
+            status: output.status.into(),
+        })
+    }
+
+    #[fallible]
+    fn cmd_exec_pty_interactive(
+        &mut self,
+        ops: &roc::RocOps,
+        cmd: &cmd::Command,
+    ) -> Result<process::ExitStatus, RocSingleTagWrapper<IOErr>> {
+        pty::interactive(cmd)
+            .map(process::ExitStatus::from)
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn cmd_exec_replace(
+        &mut self,
+        ops: &roc::RocOps,
+        cmd: &cmd::Command,
+    ) -> Result<(), RocSingleTagWrapper<IOErr>> {
+        let e = cmd.exec_replace();
+        Err(IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn cmd_pipeline(
+        &mut self,
+        ops: &roc::RocOps,
+        cmds: &RocList<cmd::Command>,
+    ) -> Result<PipelineOutputSuccess, PipelineErr> {
+        let output = cmd::pipeline(cmds.as_slice())
+            .map_err(|e| PipelineErr::cmd_err(IOErr::from_io_error(&e, ops)))?;
+
+        let statuses: Vec<process::ExitStatus> = output
+            .statuses
+            .iter()
+            .map(|&status| status.into())
+            .collect();
+        let statuses = RocList::from_slice(&statuses, ops);
+        let stdout = RocStr::from_str(&String::from_utf8_lossy(&output.stdout), ops);
+        let stderr = RocStr::from_str(&String::from_utf8_lossy(&output.stderr), ops);
+        if output.success() {
+            Ok(PipelineOutputSuccess {
+                statuses,
+                stderr_utf8_lossy: stderr,
+                stdout_utf8: stdout,
+            })
+        } else {
+            Err(PipelineErr::non_zero_exit(statuses, stderr, stdout))
         }
     }
 
+    #[fallible]
+    fn cmd_spawn(
+        &mut self,
+        ops: &roc::RocOps,
+        cmd: &cmd::Command,
+    ) -> Result<process::Process, RocSingleTagWrapper<IOErr>> {
+        cmd.spawn()
+            .map(|(child, captured)| self.processes.insert(child, captured))
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn cmd_spawn_piped(
+        &mut self,
+        ops: &roc::RocOps,
+        cmd: &cmd::Command,
+    ) -> Result<process::Process, RocSingleTagWrapper<IOErr>> {
+        cmd.spawn_piped()
+            .map(|(child, captured)| self.processes.insert(child, captured))
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
     #[fallible]
     fn dir_create(
         &mut self,
@@ -162,6 +499,14 @@ impl Host {
         RocStr::from_str(&value, ops)
     }
 
+    fn env_args_bytes(&mut self, ops: &roc::RocOps) -> RocList<RocList<u8>> {
+        let args: Vec<_> = env::args_bytes()
+            .iter()
+            .map(|arg| RocList::from_slice(arg, ops))
+            .collect();
+        RocList::from_slice(&args, ops)
+    }
+
     fn env_cwd(&mut self, ops: &roc::RocOps) -> RocStr {
         let cwd = std::env::current_dir()
             .map(|p| p.to_string_lossy().into_owned())
@@ -176,6 +521,106 @@ impl Host {
         RocStr::from_str(&exe_path, ops)
     }
 
+    fn env_pid(&mut self, _ops: &roc::RocOps) -> u32 {
+        std::process::id()
+    }
+
+    fn env_ppid(&mut self, _ops: &roc::RocOps) -> u32 {
+        std::os::unix::process::parent_id()
+    }
+
+    fn env_uid(&mut self, _ops: &roc::RocOps) -> u32 {
+        unsafe { libc::getuid() }
+    }
+
+    fn env_gid(&mut self, _ops: &roc::RocOps) -> u32 {
+        unsafe { libc::getgid() }
+    }
+
+    fn env_euid(&mut self, _ops: &roc::RocOps) -> u32 {
+        unsafe { libc::geteuid() }
+    }
+
+    fn env_username(&mut self, ops: &roc::RocOps) -> RocStr {
+        let username = env::username(unsafe { libc::getuid() }).unwrap_or_default();
+        RocStr::from_str(&username, ops)
+    }
+
+    fn env_hostname(&mut self, ops: &roc::RocOps) -> RocStr {
+        let hostname = env::hostname().unwrap_or_default();
+        RocStr::from_str(&hostname, ops)
+    }
+
+    fn env_home_dir(&mut self, ops: &roc::RocOps) -> RocStr {
+        let home_dir = env::home_dir()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        RocStr::from_str(&home_dir, ops)
+    }
+
+    fn env_config_dir(&mut self, ops: &roc::RocOps) -> RocStr {
+        let config_dir = env::xdg_dir("XDG_CONFIG_HOME", ".config")
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        RocStr::from_str(&config_dir, ops)
+    }
+
+    fn env_cache_dir(&mut self, ops: &roc::RocOps) -> RocStr {
+        let cache_dir = env::xdg_dir("XDG_CACHE_HOME", ".cache")
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        RocStr::from_str(&cache_dir, ops)
+    }
+
+    fn env_data_dir(&mut self, ops: &roc::RocOps) -> RocStr {
+        let data_dir = env::xdg_dir("XDG_DATA_HOME", ".local/share")
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        RocStr::from_str(&data_dir, ops)
+    }
+
+    fn env_runtime_dir(&mut self, ops: &roc::RocOps) -> RocStr {
+        let runtime_dir = env::runtime_dir()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        RocStr::from_str(&runtime_dir, ops)
+    }
+
+    fn env_temp_dir(&mut self, ops: &roc::RocOps) -> RocStr {
+        let temp_dir = env::temp_dir().to_string_lossy().into_owned();
+        RocStr::from_str(&temp_dir, ops)
+    }
+
+    #[fallible]
+    fn env_read_dotenv(
+        &mut self,
+        ops: &roc::RocOps,
+        path: &RocStr,
+    ) -> Result<RocList<env::EnvPair>, RocSingleTagWrapper<IOErr>> {
+        std::fs::read_to_string(path.as_str())
+            .and_then(|src| env::parse_dotenv(&src))
+            .map(|pairs| {
+                let pairs: Vec<_> = pairs
+                    .iter()
+                    .map(|(key, value)| env::EnvPair::new(key, value, ops))
+                    .collect();
+                RocList::from_slice(&pairs, ops)
+            })
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn env_load_dotenv(
+        &mut self,
+        ops: &roc::RocOps,
+        path: &RocStr,
+    ) -> Result<(), RocSingleTagWrapper<IOErr>> {
+        std::fs::read_to_string(path.as_str())
+            .and_then(|src| env::parse_dotenv(&src))
+            .and_then(env::load)
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
     #[fallible]
     fn file_read_bytes(
         &mut self,
@@ -229,46 +674,108 @@ impl Host {
     }
 
     #[fallible]
-    fn http_send(
+    fn http_client(
+        &mut self,
+        ops: &roc::RocOps,
+        config: &http::ClientConfig,
+    ) -> Result<http::Client, http::SendErr> {
+        self.http.insert(config, ops)
+    }
+
+    fn http_clear_cookies(&mut self, _ops: &roc::RocOps, client: &http::Client) {
+        if let Ok(cookies) = self.http.cookies(client) {
+            cookies.clear();
+        }
+    }
+
+    fn http_cookies(
+        &mut self,
+        ops: &roc::RocOps,
+        client: &http::Client,
+    ) -> RocList<cookie::Cookie> {
+        self.http
+            .cookies(client)
+            .map_or_else(|_| RocList::empty(), |cookies| cookies.to_roc(ops))
+    }
+
+    #[fallible]
+    fn http_download(
         &mut self,
         ops: &roc::RocOps,
         request: &http::Request,
-    ) -> Result<http::Response, RocSingleTagWrapper<http::RequestErr>> {
-        let request_builder = reqwest::blocking::Client::builder()
-            .build()
-            .map_err(|e| RocSingleTagWrapper::new(e.into()))?
-            .request(
-                match request.method.tag {
-                    http::MethodTag::Options => reqwest::Method::OPTIONS,
-                    http::MethodTag::Get => reqwest::Method::GET,
-                    http::MethodTag::Post => reqwest::Method::POST,
-                    http::MethodTag::Put => reqwest::Method::PUT,
-                    http::MethodTag::Delete => reqwest::Method::DELETE,
-                    http::MethodTag::Head => reqwest::Method::HEAD,
-                    http::MethodTag::Trace => reqwest::Method::TRACE,
-                    http::MethodTag::Connect => reqwest::Method::CONNECT,
-                    http::MethodTag::Patch => reqwest::Method::PATCH,
-                    http::MethodTag::Extension => reqwest::Method::from_bytes(
-                        request.method.extension().expect("is extension").as_bytes(),
-                    )
-                    .map_err(|_| http::RequestErr::invalid_method())?,
-                },
-                request.uri.as_str(),
-            )
-            .body(request.body.as_slice().to_vec());
-
-        let headers = request
-            .headers
-            .iter()
-            .map(|header| header.try_into())
-            .collect::<Result<Vec<(HeaderName, HeaderValue)>, _>>()?;
+        path: &RocStr,
+    ) -> Result<http::Response, http::SendErr> {
+        let sent = self.http.execute(None, request, ops)?;
+        http::download(sent, path.as_str(), ops)
+    }
 
-        let response = request_builder
-            .headers(HeaderMap::from_iter(headers.into_iter()))
-            .send()
-            .map_err(|e| RocSingleTagWrapper::new(e.into()))?;
+    #[fallible]
+    fn http_start_download(
+        &mut self,
+        ops: &roc::RocOps,
+        request: &http::Request,
+        path: &RocStr,
+    ) -> Result<http::Download, http::SendErr> {
+        let sent = self.http.execute(None, request, ops)?;
+        self.downloads
+            .insert(sent, path.as_str())
+            .map_err(|e| http::SendErr::file_err(&e, ops))
+    }
+
+    #[fallible]
+    fn http_download_next(
+        &mut self,
+        ops: &roc::RocOps,
+        download: &http::Download,
+    ) -> Result<http::DownloadStep, http::SendErr> {
+        self.downloads.next(download, ops)
+    }
 
-        Ok(Response::from_reqwest_response(response, ops))
+    #[fallible]
+    fn http_load_cookies(
+        &mut self,
+        ops: &roc::RocOps,
+        client: &http::Client,
+        path: &RocStr,
+    ) -> Result<(), RocSingleTagWrapper<IOErr>> {
+        self.http
+            .cookies(client)
+            .and_then(|cookies| {
+                std::fs::read_to_string(path.as_str()).and_then(|src| cookies.load(&src))
+            })
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn http_save_cookies(
+        &mut self,
+        ops: &roc::RocOps,
+        client: &http::Client,
+        path: &RocStr,
+    ) -> Result<(), RocSingleTagWrapper<IOErr>> {
+        self.http
+            .cookies(client)
+            .and_then(|cookies| std::fs::write(path.as_str(), cookies.save()))
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn http_send(
+        &mut self,
+        ops: &roc::RocOps,
+        request: &http::Request,
+    ) -> Result<http::Response, http::SendErr> {
+        self.http.send(None, request, ops)
+    }
+
+    #[fallible]
+    fn http_send_with(
+        &mut self,
+        ops: &roc::RocOps,
+        client: &http::Client,
+        request: &http::Request,
+    ) -> Result<http::Response, http::SendErr> {
+        self.http.send(Some(client), request, ops)
     }
 
     #[fallible]
@@ -307,6 +814,71 @@ impl Host {
             .map_err(|e| IOErr::from_io_error(&e, ops).into())
     }
 
+    #[fallible]
+    fn process_read_line(
+        &mut self,
+        ops: &roc::RocOps,
+        process: &process::Process,
+    ) -> Result<process::ReadLine, RocSingleTagWrapper<IOErr>> {
+        self.processes
+            .read_line(process)
+            .map(|line| match line {
+                Some(line) => process::ReadLine::from_line(line, ops),
+                None => process::ReadLine::end(),
+            })
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn process_wait(
+        &mut self,
+        ops: &roc::RocOps,
+        process: &process::Process,
+    ) -> Result<process::ExitStatus, RocSingleTagWrapper<IOErr>> {
+        self.processes
+            .wait(process)
+            .map(process::ExitStatus::from)
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn process_try_wait(
+        &mut self,
+        ops: &roc::RocOps,
+        process: &process::Process,
+    ) -> Result<process::TryWait, RocSingleTagWrapper<IOErr>> {
+        self.processes
+            .try_wait(process)
+            .map(|status| match status {
+                Some(status) => process::TryWait::finished(status.into()),
+                None => process::TryWait::running(),
+            })
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn process_kill(
+        &mut self,
+        ops: &roc::RocOps,
+        process: &process::Process,
+    ) -> Result<(), RocSingleTagWrapper<IOErr>> {
+        self.processes
+            .kill(process)
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
+    #[fallible]
+    fn process_signal(
+        &mut self,
+        ops: &roc::RocOps,
+        process: &process::Process,
+        signal: &i32,
+    ) -> Result<(), RocSingleTagWrapper<IOErr>> {
+        self.processes
+            .signal(process, *signal)
+            .map_err(|e| IOErr::from_io_error(&e, ops).into())
+    }
+
     #[fallible]
     fn random_seed_u64(&mut self, ops: &roc::RocOps) -> Result<u64, RocSingleTagWrapper<IOErr>> {
         Ok(roc_random::random_u64(ops)?)
@@ -389,7 +961,7 @@ fn init(args: &[String]) -> ExitCode {
         )
         .init();
 
-    let host = RocHost::builder().build(Host);
+    let host = RocHost::builder().build(Host::default());
 
     host.run(args)
 }
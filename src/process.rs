@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Read},
+    mem::MaybeUninit,
+    os::unix::process::ExitStatusExt,
+    process::Child,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+use roc_platform_builder::roc_std_new::{RocOps, RocRefcounted, RocStr, roc_refcounted_noop_impl};
+
+use crate::cmd::Captured;
+
+/// Handle to a spawned child process, as seen by Roc.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Process {
+    /// key into [`ProcessTable`]
+    id: u64,
+    pid: u32,
+}
+
+roc_refcounted_noop_impl!(Process);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SignaledPayload {
+    pub signal: i32,       // offset 0 (4 bytes)
+    pub core_dumped: bool, // offset 4 (1 byte + padding)
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union ExitStatusPayload {
+    exited: i32,
+    signaled: SignaledPayload,
+}
+
+/// `[Exited(I32), Signaled({ core_dumped : Bool, signal : I32 })]`
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ExitStatus {
+    payload: ExitStatusPayload,
+    discriminant: u8, // Exited=0, Signaled=1
+}
+
+impl ExitStatus {
+    pub fn exited(exit_code: i32) -> Self {
+        Self {
+            payload: ExitStatusPayload { exited: exit_code },
+            discriminant: 0,
+        }
+    }
+
+    pub fn signaled(signal: i32, core_dumped: bool) -> Self {
+        Self {
+            payload: ExitStatusPayload {
+                signaled: SignaledPayload {
+                    signal,
+                    core_dumped,
+                },
+            },
+            discriminant: 1,
+        }
+    }
+}
+
+impl From<std::process::ExitStatus> for ExitStatus {
+    fn from(status: std::process::ExitStatus) -> Self {
+        match (status.code(), status.signal()) {
+            (Some(exit_code), _) => Self::exited(exit_code),
+            (None, Some(signal)) => Self::signaled(signal, status.core_dumped()),
+            (None, None) => unreachable!("waited-for processes either exit or are signaled"),
+        }
+    }
+}
+
+impl core::fmt::Debug for ExitStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.discriminant == 0 {
+            write!(f, "ExitStatus::Exited({})", unsafe { self.payload.exited })
+        } else {
+            write!(f, "ExitStatus::Signaled({:?})", unsafe {
+                self.payload.signaled
+            })
+        }
+    }
+}
+
+roc_refcounted_noop_impl!(ExitStatus);
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[repr(u8)]
+pub enum TryWaitTag {
+    Finished = 0,
+    Running = 1,
+}
+
+impl core::fmt::Debug for TryWaitTag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Finished => f.write_str("TryWaitTag::Finished"),
+            Self::Running => f.write_str("TryWaitTag::Running"),
+        }
+    }
+}
+
+roc_refcounted_noop_impl!(TryWaitTag);
+
+#[repr(C)]
+pub struct TryWait {
+    ///only valid for the `Finished` variant (tag == 0)
+    status: MaybeUninit<ExitStatus>,
+    pub tag: TryWaitTag,
+}
+
+impl TryWait {
+    pub fn finished(status: ExitStatus) -> Self {
+        Self {
+            status: MaybeUninit::new(status),
+            tag: TryWaitTag::Finished,
+        }
+    }
+
+    pub fn running() -> Self {
+        Self {
+            status: MaybeUninit::zeroed(),
+            tag: TryWaitTag::Running,
+        }
+    }
+}
+
+roc_refcounted_noop_impl!(TryWait);
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[repr(u8)]
+pub enum ReadLineTag {
+    End = 0,
+    Stderr = 1,
+    Stdout = 2,
+}
+
+impl core::fmt::Debug for ReadLineTag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::End => f.write_str("ReadLineTag::End"),
+            Self::Stderr => f.write_str("ReadLineTag::Stderr"),
+            Self::Stdout => f.write_str("ReadLineTag::Stdout"),
+        }
+    }
+}
+
+roc_refcounted_noop_impl!(ReadLineTag);
+
+/// `[End, Stderr(Str), Stdout(Str)]`
+#[repr(C)]
+pub struct ReadLine {
+    line: MaybeUninit<RocStr>,
+    pub tag: ReadLineTag,
+}
+
+impl ReadLine {
+    pub fn end() -> Self {
+        Self {
+            line: MaybeUninit::zeroed(),
+            tag: ReadLineTag::End,
+        }
+    }
+
+    pub fn from_line(line: Line, ops: &RocOps) -> Self {
+        let (tag, bytes) = match line {
+            Line::Stderr(bytes) => (ReadLineTag::Stderr, bytes),
+            Line::Stdout(bytes) => (ReadLineTag::Stdout, bytes),
+        };
+        Self {
+            line: MaybeUninit::new(RocStr::from_str(&String::from_utf8_lossy(&bytes), ops)),
+            tag,
+        }
+    }
+}
+
+impl RocRefcounted for ReadLine {
+    fn inc(&mut self) {
+        if self.tag != ReadLineTag::End {
+            unsafe { self.line.assume_init_mut().inc() };
+        }
+    }
+    fn dec(&mut self) {
+        if self.tag != ReadLineTag::End {
+            unsafe { self.line.assume_init_mut().dec() };
+        }
+    }
+    fn is_refcounted() -> bool {
+        true
+    }
+}
+
+/// A line of output of a spawned process, without the trailing `\n` or `\r\n`.
+pub enum Line {
+    Stderr(Vec<u8>),
+    Stdout(Vec<u8>),
+}
+
+struct Spawned {
+    child: Child,
+    /// the lines of stdout and stderr, if they are captured
+    lines: Option<Receiver<io::Result<Line>>>,
+}
+
+/// The children spawned by `Cmd.spawn!` that have not been waited for yet.
+#[derive(Default)]
+pub struct ProcessTable {
+    children: HashMap<u64, Spawned>,
+    next_id: u64,
+}
+
+impl ProcessTable {
+    /// Takes ownership of the child. Its captured stdout and stderr are read
+    /// line by line on background threads, so the child never blocks on a
+    /// full pipe, no matter how slowly the lines are consumed.
+    pub fn insert(&mut self, child: Child, captured: Captured) -> Process {
+        let process = Process {
+            id: self.next_id,
+            pid: child.id(),
+        };
+        self.next_id += 1;
+
+        let Captured { stdout, stderr } = captured;
+        let lines = (stdout.is_some() || stderr.is_some()).then(|| {
+            let (sender, receiver) = mpsc::channel();
+            if let Some(stdout) = stdout {
+                let sender = sender.clone();
+                std::thread::spawn(move || forward_lines(stdout, Line::Stdout, sender));
+            }
+            if let Some(stderr) = stderr {
+                std::thread::spawn(move || forward_lines(stderr, Line::Stderr, sender));
+            }
+            receiver
+        });
+
+        self.children.insert(process.id, Spawned { child, lines });
+        process
+    }
+
+    /// Blocks until the next line of stdout or stderr is available.
+    ///
+    /// Returns `None` once both streams are closed, or right away if neither
+    /// is captured.
+    pub fn read_line(&mut self, process: &Process) -> io::Result<Option<Line>> {
+        match &self.get_mut(process)?.lines {
+            Some(lines) => lines.recv().ok().transpose(),
+            None => Ok(None),
+        }
+    }
+
+    /// Blocks until the process exits and releases its handle.
+    pub fn wait(&mut self, process: &Process) -> io::Result<std::process::ExitStatus> {
+        let status = self.get_mut(process)?.child.wait()?;
+        self.children.remove(&process.id);
+        Ok(status)
+    }
+
+    /// Returns the exit status if the process has exited, releasing its handle.
+    pub fn try_wait(&mut self, process: &Process) -> io::Result<Option<std::process::ExitStatus>> {
+        let status = self.get_mut(process)?.child.try_wait()?;
+        if status.is_some() {
+            self.children.remove(&process.id);
+        }
+        Ok(status)
+    }
+
+    /// Kills the process with `SIGKILL`, waits for it and releases its handle.
+    pub fn kill(&mut self, process: &Process) -> io::Result<()> {
+        let child = &mut self.get_mut(process)?.child;
+        child.kill()?;
+        child.wait()?;
+        self.children.remove(&process.id);
+        Ok(())
+    }
+
+    /// Sends `signal` to the process.
+    ///
+    /// Only processes that have not been waited for can be signalled, which
+    /// makes sure the pid has not been reused by an unrelated process.
+    pub fn signal(&mut self, process: &Process, signal: i32) -> io::Result<()> {
+        let pid = self.get_mut(process)?.child.id();
+        if unsafe { libc::kill(pid as libc::pid_t, signal) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn get_mut(&mut self, process: &Process) -> io::Result<&mut Spawned> {
+        self.children.get_mut(&process.id).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("process {} was already waited for", process.pid),
+            )
+        })
+    }
+}
+
+/// Sends every line read from `reader` until it is closed or the receiving
+/// process handle has been released.
+fn forward_lines(reader: impl Read, line: fn(Vec<u8>) -> Line, sender: Sender<io::Result<Line>>) {
+    let mut reader = BufReader::new(reader);
+    loop {
+        let mut buf = Vec::new();
+        let result = match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => return,
+            Ok(_) => {
+                if buf.ends_with(b"\n") {
+                    buf.pop();
+                    if buf.ends_with(b"\r") {
+                        buf.pop();
+                    }
+                }
+                Ok(line(buf))
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => Err(e),
+        };
+        let failed = result.is_err();
+        if sender.send(result).is_err() || failed {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forward_lines_strips_line_endings() {
+        let (sender, receiver) = mpsc::channel();
+        forward_lines(
+            &b"unix\nwindows\r\n\r\nbare\rcr\nlast\r"[..],
+            Line::Stdout,
+            sender,
+        );
+
+        let lines: Vec<Vec<u8>> = receiver
+            .iter()
+            .map(|line| match line.unwrap() {
+                Line::Stdout(bytes) => bytes,
+                Line::Stderr(_) => panic!("stdout was read as stderr"),
+            })
+            .collect();
+        let expected: [&[u8]; 5] = [b"unix", b"windows", b"", b"bare\rcr", b"last\r"];
+        assert_eq!(lines, expected);
+    }
+}
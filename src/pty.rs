@@ -0,0 +1,365 @@
+use std::{
+    fs::File,
+    io::{self, IsTerminal, Read, Write},
+    os::{
+        fd::{AsFd, AsRawFd, FromRawFd, OwnedFd},
+        unix::process::CommandExt,
+    },
+    process::{Child, ExitStatus},
+};
+
+use roc_platform_builder::roc_std_new::roc_refcounted_noop_impl;
+
+use crate::cmd;
+
+/// The window size used for interactive commands if the size of the current
+/// terminal is unknown.
+const FALLBACK_WINDOW_SIZE: WindowSize = WindowSize { cols: 80, rows: 24 };
+
+/// How long to wait for input before checking whether the window of the
+/// current terminal was resized.
+const POLL_TIMEOUT_MILLIS: libc::c_int = 100;
+
+/// `{ cols : U16, rows : U16 }`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+roc_refcounted_noop_impl!(WindowSize);
+
+impl WindowSize {
+    /// The size of the terminal the current process is attached to.
+    fn current() -> Option<Self> {
+        crossterm::terminal::size()
+            .ok()
+            .map(|(cols, rows)| Self { cols, rows })
+    }
+
+    fn to_winsize(self) -> libc::winsize {
+        libc::winsize {
+            ws_row: self.rows,
+            ws_col: self.cols,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        }
+    }
+}
+
+/// Runs the command on a new pseudo-terminal of the given size and collects
+/// everything it writes to the terminal in `stdout`.
+///
+/// The stdio redirects of the command are ignored: stdin, stdout and stderr
+/// are all the terminal. Stdin bytes are typed into the terminal, so they are
+/// echoed into the output like keyboard input would be, followed by the end
+/// of file character.
+pub fn output(command: &cmd::Command, size: WindowSize) -> io::Result<cmd::Output> {
+    let (master, mut child) = spawn(command, size)?;
+
+    let input = command.stdin.bytes().map(|bytes| bytes.as_slice());
+    let writer = match input {
+        Some(_) => Some(master.try_clone()?),
+        None => None,
+    };
+    std::thread::scope(|s| {
+        let writer = match (writer, input) {
+            (Some(writer), Some(input)) => Some(s.spawn(move || write_input(writer, input))),
+            _ => None,
+        };
+        let reader = s.spawn(|| read_master(&master));
+
+        // On timeout, this stops the whole process group of the child, which
+        // is also its session, so no background process keeps the terminal
+        // open and the reader from finishing.
+        let (status, timed_out) = cmd::wait_timeout(&mut child, command.timeout())?;
+        let stdout = reader.join().expect("terminal reader panicked")?;
+        if let Some(writer) = writer {
+            writer.join().expect("terminal writer panicked")?;
+        }
+
+        Ok(cmd::Output {
+            status,
+            stdout,
+            stderr: Vec::new(),
+            timed_out,
+        })
+    })
+}
+
+/// Runs the command on a new pseudo-terminal that is connected to the
+/// terminal of the current process, and waits for it to exit.
+///
+/// The current terminal is put into raw mode while the command runs, so every
+/// key press goes straight to the command, and the size of the command's
+/// terminal follows that of the current one. Timeouts are ignored.
+pub fn interactive(command: &cmd::Command) -> io::Result<ExitStatus> {
+    let size = WindowSize::current().unwrap_or(FALLBACK_WINDOW_SIZE);
+    let (master, mut child) = spawn(command, size)?;
+
+    let raw_mode = io::stdin().is_terminal() && crossterm::terminal::enable_raw_mode().is_ok();
+    let relayed = relay(&master, size);
+    if raw_mode {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+
+    if let Err(e) = relayed {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(e);
+    }
+    child.wait()
+}
+
+/// Opens a pseudo-terminal and starts the command on it, as the leader of a
+/// new session with the terminal as its controlling terminal.
+///
+/// Returns the master side of the terminal.
+fn spawn(command: &cmd::Command, size: WindowSize) -> io::Result<(File, Child)> {
+    let (master, slave) = open(size)?;
+
+    let mut process = std::process::Command::from(command);
+    process
+        .stdin(slave.try_clone()?)
+        .stdout(slave.try_clone()?)
+        .stderr(slave);
+    // SAFETY: only async-signal-safe functions are called between fork and exec.
+    unsafe {
+        process.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::ioctl(libc::STDIN_FILENO, libc::TIOCSCTTY, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let child = process.spawn()?;
+    // Closes our copies of the slave, so that reading the master ends when
+    // the child exits.
+    drop(process);
+
+    Ok((master, child))
+}
+
+fn open(size: WindowSize) -> io::Result<(File, OwnedFd)> {
+    let mut master = -1;
+    let mut slave = -1;
+    let winsize = size.to_winsize();
+    let result = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            &winsize,
+        )
+    };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: openpty succeeded, so both are open file descriptors we own.
+    let (master, slave) = unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+
+    // Keep other children spawned in the meantime from holding the terminal open.
+    for fd in [&master, &slave] {
+        if unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok((File::from(master), slave))
+}
+
+fn set_window_size(master: &File, size: WindowSize) -> io::Result<()> {
+    let winsize = size.to_winsize();
+    if unsafe { libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &winsize) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Types the input into the terminal, followed by the end of file character
+/// (usually Ctrl-D), so that programs reading until the end of their input
+/// finish.
+fn write_input(mut master: File, input: &[u8]) -> io::Result<()> {
+    let result = eof_char(&master).and_then(|eof| {
+        master.write_all(input)?;
+        // The first one only ends a partial line.
+        if !input.is_empty() && !input.ends_with(b"\n") {
+            master.write_all(&[eof])?;
+        }
+        master.write_all(&[eof])
+    });
+    match result {
+        // The terminal was closed because the child is gone, which only means
+        // it did not read all of its input.
+        Err(e) if e.raw_os_error() == Some(libc::EIO) || e.kind() == io::ErrorKind::BrokenPipe => {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// The character that ends the input of the terminal in canonical mode.
+fn eof_char(master: &File) -> io::Result<u8> {
+    let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+    if unsafe { libc::tcgetattr(master.as_raw_fd(), termios.as_mut_ptr()) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { termios.assume_init() }.c_cc[libc::VEOF])
+}
+
+/// Reads the master until the terminal is closed.
+fn read_master(mut master: &File) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        match read_chunk(&mut master, &mut buf)? {
+            Some(n) => output.extend_from_slice(&buf[..n]),
+            None => return Ok(output),
+        }
+    }
+}
+
+/// Reads from the master, returning `None` once the terminal is closed.
+///
+/// Linux reports a closed terminal with `EIO` rather than end of file.
+fn read_chunk(master: &mut &File, buf: &mut [u8]) -> io::Result<Option<usize>> {
+    loop {
+        match master.read(buf) {
+            Ok(0) => return Ok(None),
+            Ok(n) => return Ok(Some(n)),
+            Err(e) if e.raw_os_error() == Some(libc::EIO) => return Ok(None),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Copies the stdin of the current process to the master and the master to
+/// the stdout of the current process, until the terminal is closed.
+fn relay(mut master: &File, mut size: WindowSize) -> io::Result<()> {
+    // Read stdin unbuffered, so no input is left behind in a buffer poll
+    // does not know about.
+    let mut stdin = File::from(io::stdin().as_fd().try_clone_to_owned()?);
+    let mut stdin_open = true;
+    let mut stdout = io::stdout();
+    let mut buf = [0; 4096];
+    loop {
+        if let Some(current) = WindowSize::current()
+            && current != size
+        {
+            size = current;
+            set_window_size(master, size)?;
+        }
+
+        let mut fds = [
+            libc::pollfd {
+                fd: master.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                // Negative file descriptors are ignored.
+                fd: if stdin_open { stdin.as_raw_fd() } else { -1 },
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+        if unsafe {
+            libc::poll(
+                fds.as_mut_ptr(),
+                fds.len() as libc::nfds_t,
+                POLL_TIMEOUT_MILLIS,
+            )
+        } == -1
+        {
+            let e = io::Error::last_os_error();
+            if e.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(e);
+        }
+
+        if fds[0].revents != 0 {
+            match read_chunk(&mut master, &mut buf)? {
+                Some(n) => {
+                    stdout.write_all(&buf[..n])?;
+                    stdout.flush()?;
+                }
+                None => return Ok(()),
+            }
+        }
+        if fds[1].revents != 0 {
+            match stdin.read(&mut buf) {
+                Ok(0) => stdin_open = false,
+                Ok(n) => master.write_all(&buf[..n])?,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use roc_platform_builder::roc_std_new::RocOps;
+
+    use super::*;
+    use crate::cmd::tests::sh;
+
+    const SIZE: WindowSize = WindowSize { cols: 80, rows: 24 };
+
+    #[test]
+    fn output() {
+        let ops = RocOps::mock();
+        let output = super::output(
+            &sh("read line; echo \"got $line\"", 0, Some(b"hi\n"), &ops),
+            SIZE,
+        )
+        .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"hi\r\ngot hi\r\n");
+    }
+
+    #[test]
+    fn input_ends() {
+        let ops = RocOps::mock();
+        for (input, expected) in [
+            (&b"a\nb\n"[..], &b"a\r\nb\r\ndone\r\n"[..]),
+            (b"a\nb", b"a\r\nbdone\r\n"),
+            (b"", b"done\r\n"),
+        ] {
+            let command = sh("cat >/dev/null; echo done", 5000, Some(input), &ops);
+            let output = super::output(&command, SIZE).unwrap();
+
+            assert!(!output.timed_out, "{input:?}");
+            assert_eq!(output.stdout, expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn unread_input() {
+        let ops = RocOps::mock();
+        let input = vec![b'x'; 1 << 20];
+        let output = super::output(&sh("exit 3", 0, Some(&input), &ops), SIZE).unwrap();
+
+        assert_eq!(output.status.code(), Some(3));
+    }
+
+    #[test]
+    fn timeout_stops_background_processes() {
+        let ops = RocOps::mock();
+        let started = Instant::now();
+        let output = super::output(&sh("sleep 100 & sleep 100", 200, None, &ops), SIZE).unwrap();
+
+        assert!(output.timed_out);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}